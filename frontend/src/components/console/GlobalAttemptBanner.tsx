@@ -62,11 +62,11 @@ export default function GlobalAttemptBanner() {
           if (!programId?.equals(VAULT_GAME_PROGRAM_ID)) continue;
 
           // Best-effort mapping based on our known account ordering.
-          // make_guess_sol: [vault, mega_vault, player_profile, player, system_program]
-          // make_guess_spl: [vault, mega_vault, player_profile, player, fee_mint, ...]
+          // make_guess_sol: [vault, mega_vault, player_profile, attempt_record, guess_record, player, system_program]
+          // make_guess_spl: [vault, mega_vault, player_profile, attempt_record, guess_record, player, fee_mint, ...]
           const acctIdxs: number[] = ix.accounts || [];
           const vault = acctIdxs[0] != null ? accountKeys[acctIdxs[0]] : undefined;
-          const player = acctIdxs[3] != null ? accountKeys[acctIdxs[3]] : undefined;
+          const player = acctIdxs[5] != null ? accountKeys[acctIdxs[5]] : undefined;
 
           setItems((prev) => {
             const next = [{ sig, player: player?.toBase58(), vault: vault?.toBase58(), ts: Date.now() }, ...prev];
@@ -0,0 +1,108 @@
+//! Merkle allowlists for `AccessMode::Allowlist` vaults.
+//!
+//! Leaves are `sha256("vault_game:allow_leaf" || wallet)`; inner nodes hash their two children in
+//! sorted order, so proofs carry no left/right flags. An unpaired node is carried up unchanged.
+//!
+//! `build_tree` / `proof` are meant for creators off-chain; `verify_proof` runs on-chain.
+
+use anchor_lang::prelude::Pubkey;
+use solana_sha256_hasher::hashv;
+
+const LEAF_TAG: &[u8] = b"vault_game:allow_leaf";
+const NODE_TAG: &[u8] = b"vault_game:allow_node";
+
+pub fn leaf(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_TAG, wallet.as_ref()]).to_bytes()
+}
+
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_TAG, lo, hi]).to_bytes()
+}
+
+/// All tree levels, leaves first (in `wallets` order).
+pub fn build_tree(wallets: &[Pubkey]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![wallets.iter().map(leaf).collect::<Vec<_>>()];
+    while let Some(top) = levels.last().filter(|l| l.len() > 1) {
+        let next = top
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+pub fn root(levels: &[Vec<[u8; 32]>]) -> [u8; 32] {
+    levels.last().and_then(|l| l.first().copied()).unwrap_or_default()
+}
+
+/// Sibling hashes for the wallet at `index`, skipping levels where it has no sibling.
+pub fn proof(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+    let mut idx = index;
+    let mut siblings = Vec::new();
+    for level in &levels[..levels.len().saturating_sub(1)] {
+        if let Some(sibling) = level.get(idx ^ 1) {
+            siblings.push(*sibling);
+        }
+        idx >>= 1;
+    }
+    siblings
+}
+
+/// Check that `wallet` is on the allowlist committed to by `root`.
+pub fn verify_proof(root: &[u8; 32], wallet: &Pubkey, siblings: &[[u8; 32]]) -> bool {
+    let acc = siblings.iter().fold(leaf(wallet), |acc, sibling| node(&acc, sibling));
+    acc == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallets(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn every_member_verifies_for_odd_and_even_list_sizes() {
+        for n in 1..=9 {
+            let wallets = wallets(n);
+            let levels = build_tree(&wallets);
+            let root = root(&levels);
+
+            for (i, wallet) in wallets.iter().enumerate() {
+                assert!(verify_proof(&root, wallet, &proof(&levels, i)), "{i} of {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn non_member_is_rejected() {
+        for n in [1, 3, 5, 7] {
+            let wallets = wallets(n);
+            let levels = build_tree(&wallets);
+            let root = root(&levels);
+            let outsider = Pubkey::new_unique();
+
+            assert!(!verify_proof(&root, &outsider, &[]));
+            for i in 0..n {
+                // A member's proof does not carry anyone else.
+                assert!(!verify_proof(&root, &outsider, &proof(&levels, i)));
+                if n > 1 {
+                    assert!(!verify_proof(&root, &wallets[(i + 1) % n], &proof(&levels, i)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_list_admits_nobody() {
+        let levels = build_tree(&[]);
+        assert!(!verify_proof(&root(&levels), &Pubkey::new_unique(), &[]));
+    }
+}
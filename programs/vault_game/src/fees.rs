@@ -0,0 +1,382 @@
+//! Attempt fee curves and fee splits.
+//!
+//! A vault's fee has two parts: the *ladder* fee stored in `Vault.current_fee_amount`, stepped once
+//! per paid attempt by `next_ladder_fee`, and the *charged* fee derived from it at guess time by
+//! `charged_fee` (only `TimeDecay` makes these differ).
+//!
+//! The ladder saturates at the vault's `max_fee_amount` (and at `u64::MAX`) instead of overflowing,
+//! so a busy vault stays guessable at a flat fee.
+//!
+//! Each charged fee is then divided by the vault's `FeeSplit`.
+//!
+//! Everything here is pure so clients can quote the Nth attempt with `attempt_fee` (and its split
+//! with `FeeSplit::split`) and get exactly what `make_guess_*` will charge.
+
+use anchor_lang::prelude::*;
+
+const BPS: u64 = 10_000;
+
+/// Legacy ladder: every attempt costs 1.2x the previous one (rounded up).
+pub const DEFAULT_FEE_CURVE: FeeCurve = FeeCurve::Geometric { ratio_bps: 12_000 };
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeCurve {
+    /// `fee_n = ceil(fee_{n-1} * ratio_bps / 10_000)`.
+    Geometric { ratio_bps: u16 },
+    /// `fee_n = fee_{n-1} + step`.
+    Linear { step: u64 },
+    /// Every attempt costs the starting fee.
+    Flat,
+    /// Geometric, but never above `cap`.
+    CappedGeometric { ratio_bps: u16, cap: u64 },
+    /// Geometric ladder, discounted by `decay_bps` per full `period_secs` since vault creation,
+    /// down to `floor_bps` of the ladder fee.
+    TimeDecay {
+        ratio_bps: u16,
+        decay_bps: u16,
+        period_secs: u32,
+        floor_bps: u16,
+    },
+}
+impl FeeCurve {
+    /// Borsh size of the largest variant (tag + fields).
+    pub const LEN: usize = 1 + 10;
+}
+
+/// Admin limits on creator-chosen curves (`GlobalConfig.fee_curve_bounds`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeCurveBounds {
+    pub min_ratio_bps: u16,
+    pub max_ratio_bps: u16,
+    /// Largest per-period discount for `TimeDecay`.
+    pub max_decay_bps: u16,
+    /// Shortest decay period for `TimeDecay`.
+    pub min_period_secs: u32,
+    /// Largest `Linear` step, in basis points of the vault's starting fee (fee mints differ in
+    /// decimals, so an absolute step would mean something different per mint).
+    pub max_linear_step_bps: u16,
+    /// Lowest `TimeDecay` floor, so a decaying vault never becomes (nearly) free to guess.
+    pub min_floor_bps: u16,
+}
+impl FeeCurveBounds {
+    pub const LEN: usize = 2 + 2 + 2 + 4 + 2 + 2;
+
+    /// Bounds used until the admin sets their own.
+    pub const DEFAULT: FeeCurveBounds = FeeCurveBounds {
+        min_ratio_bps: 10_000,
+        max_ratio_bps: 20_000,
+        max_decay_bps: 1_000,
+        min_period_secs: 3_600,
+        max_linear_step_bps: 10_000,
+        min_floor_bps: 1_000,
+    };
+
+    /// A zero `min_period_secs` would let `TimeDecay` discount every second; a `Linear` step is
+    /// capped at the starting fee itself.
+    pub fn is_valid(&self) -> bool {
+        self.min_ratio_bps as u64 >= BPS
+            && self.min_ratio_bps <= self.max_ratio_bps
+            && self.max_decay_bps as u64 <= BPS
+            && self.min_period_secs > 0
+            && self.max_linear_step_bps as u64 <= BPS
+            && (1..=BPS).contains(&(self.min_floor_bps as u64))
+    }
+
+    /// Whether `curve` (starting at `starting_fee`) stays within these bounds.
+    pub fn allows(&self, curve: &FeeCurve, starting_fee: u64) -> bool {
+        let ratio_ok = |r: u16| (self.min_ratio_bps..=self.max_ratio_bps).contains(&r);
+        match *curve {
+            FeeCurve::Geometric { ratio_bps } => ratio_ok(ratio_bps),
+            FeeCurve::Linear { step } => step <= mul_bps_ceil(starting_fee, self.max_linear_step_bps as u64),
+            FeeCurve::Flat => true,
+            FeeCurve::CappedGeometric { ratio_bps, cap } => ratio_ok(ratio_bps) && cap >= starting_fee,
+            FeeCurve::TimeDecay {
+                ratio_bps,
+                decay_bps,
+                period_secs,
+                floor_bps,
+            } => {
+                ratio_ok(ratio_bps)
+                    && decay_bps <= self.max_decay_bps
+                    && period_secs >= self.min_period_secs
+                    && (self.min_floor_bps..=BPS as u16).contains(&floor_bps)
+            }
+        }
+    }
+}
+
+/// Starting-fee multiplier for a PIN length (shorter PINs are easier, so they start higher).
+pub fn start_multiplier(pin_len: u8) -> u64 {
+    match pin_len {
+        3 => 100,
+        4 => 25,
+        5 => 10,
+        6 => 10,
+        8 => 1,
+        _ => 10,
+    }
+}
+
+/// First ladder fee for a creator's base fee. `None` on overflow.
+pub fn starting_fee(base_fee: u64, pin_len: u8) -> Option<u64> {
+    base_fee.checked_mul(start_multiplier(pin_len))
+}
+
+/// `ceil(value * bps / 10_000)`, saturating at `u64::MAX`.
+fn mul_bps_ceil(value: u64, bps: u64) -> u64 {
+    let n = (value as u128) * (bps as u128);
+    u64::try_from(n.div_ceil(BPS as u128)).unwrap_or(u64::MAX)
+}
+
+/// Ladder fee after one more paid attempt, never above `max_fee`.
+pub fn next_ladder_fee(curve: &FeeCurve, prev: u64, max_fee: u64) -> u64 {
+    let next = match *curve {
+        FeeCurve::Geometric { ratio_bps } | FeeCurve::TimeDecay { ratio_bps, .. } => mul_bps_ceil(prev, ratio_bps as u64),
+        FeeCurve::Linear { step } => prev.saturating_add(step),
+        FeeCurve::Flat => prev,
+        FeeCurve::CappedGeometric { ratio_bps, cap } => mul_bps_ceil(prev, ratio_bps as u64).min(cap),
+    };
+    next.min(max_fee)
+}
+
+/// Fee actually charged for a ladder fee, `elapsed_secs` after vault creation.
+pub fn charged_fee(curve: &FeeCurve, ladder_fee: u64, elapsed_secs: i64) -> u64 {
+    match *curve {
+        FeeCurve::TimeDecay {
+            decay_bps,
+            period_secs,
+            floor_bps,
+            ..
+        } if period_secs > 0 => {
+            let periods = elapsed_secs.max(0) as u64 / period_secs as u64;
+            let discount = periods.saturating_mul(decay_bps as u64);
+            let keep_bps = BPS.saturating_sub(discount).max(floor_bps as u64);
+            // keep_bps <= BPS, so this never exceeds `ladder_fee`.
+            mul_bps_ceil(ladder_fee, keep_bps)
+        }
+        _ => ladder_fee,
+    }
+}
+
+/// Ladder fee of the attempt with index `attempt_index` (0-based), assuming every earlier attempt paid.
+pub fn ladder_fee(curve: &FeeCurve, starting_fee: u64, max_fee: u64, attempt_index: u64) -> u64 {
+    let mut fee = starting_fee;
+    for _ in 0..attempt_index {
+        let next = next_ladder_fee(curve, fee, max_fee);
+        if next == fee {
+            // Flat, saturated or a free geometric ladder: every later attempt costs the same.
+            break;
+        }
+        fee = next;
+    }
+    fee
+}
+
+/// What `make_guess_*` charges for attempt `attempt_index` made `elapsed_secs` after vault creation.
+pub fn attempt_fee(curve: &FeeCurve, starting_fee: u64, max_fee: u64, attempt_index: u64, elapsed_secs: i64) -> u64 {
+    charged_fee(curve, ladder_fee(curve, starting_fee, max_fee, attempt_index), elapsed_secs)
+}
+
+/// Shares of each attempt fee, in basis points summing to 10_000.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    /// Vault pool paid to the winner (or split 50/50 creator/mega on reclaim).
+    pub winner_bps: u16,
+    /// Mega vault pool for the vault's fee currency.
+    pub mega_bps: u16,
+    /// Protocol treasury PDA.
+    pub treasury_bps: u16,
+    /// Paid straight to the vault creator.
+    pub creator_bps: u16,
+}
+
+/// One attempt fee divided per `FeeSplit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeParts {
+    pub winner: u64,
+    pub mega: u64,
+    pub treasury: u64,
+    pub creator: u64,
+}
+
+impl FeeSplit {
+    pub const LEN: usize = 2 * 4;
+
+    /// Legacy economics: 80% vault pool (winner), 20% mega vault.
+    pub const DEFAULT: FeeSplit = FeeSplit {
+        winner_bps: 8_000,
+        mega_bps: 2_000,
+        treasury_bps: 0,
+        creator_bps: 0,
+    };
+
+    pub fn is_valid(&self) -> bool {
+        self.winner_bps as u64 + self.mega_bps as u64 + self.treasury_bps as u64 + self.creator_bps as u64 == BPS
+    }
+
+    /// Divide `fee`. Winner, treasury and creator shares round down; the mega vault takes the dust.
+    pub fn split(&self, fee: u64) -> FeeParts {
+        let share = |bps: u16| ((fee as u128 * bps as u128) / BPS as u128) as u64;
+        let winner = share(self.winner_bps);
+        let treasury = share(self.treasury_bps);
+        let creator = share(self.creator_bps);
+        FeeParts {
+            winner,
+            mega: fee - winner - treasury - creator,
+            treasury,
+            creator,
+        }
+    }
+}
+
+/// Admin limits on per-vault `FeeSplit` overrides (`GlobalConfig.fee_split_bounds`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplitBounds {
+    pub min_mega_bps: u16,
+    pub min_treasury_bps: u16,
+    pub max_creator_bps: u16,
+}
+
+impl FeeSplitBounds {
+    pub const LEN: usize = 2 * 3;
+
+    /// Bounds used until the admin sets their own: no creator share, mega keeps at least 20%.
+    pub const DEFAULT: FeeSplitBounds = FeeSplitBounds {
+        min_mega_bps: 2_000,
+        min_treasury_bps: 0,
+        max_creator_bps: 0,
+    };
+
+    pub fn allows(&self, split: &FeeSplit) -> bool {
+        split.is_valid()
+            && split.mega_bps >= self.min_mega_bps
+            && split.treasury_bps >= self.min_treasury_bps
+            && split.creator_bps <= self.max_creator_bps
+    }
+}
+
+/// Referral cut carved out of an attempt's mega share (`GlobalConfig.referral_bps`), rounded down.
+pub fn referral_cut(mega_share: u64, referral_bps: u16) -> u64 {
+    ((mega_share as u128 * referral_bps.min(BPS as u16) as u128) / BPS as u128) as u64
+}
+
+/// Winner pool of a token vault at payout. Prize and pool share the vault's token account, so the
+/// pool is taken from one balance read before anything is paid out.
+///
+/// `prize_amount` was booked net of inbound transfer fees, so the balance always covers it.
+pub fn vault_pool(balance: u64, prize_amount: u64) -> Option<u64> {
+    balance.checked_sub(prize_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const B: FeeCurveBounds = FeeCurveBounds::DEFAULT;
+
+    #[test]
+    fn linear_step_is_bounded_by_the_starting_fee() {
+        assert!(B.allows(&FeeCurve::Linear { step: 1_000 }, 1_000));
+        assert!(!B.allows(&FeeCurve::Linear { step: 1_001 }, 1_000));
+        assert!(!B.allows(&FeeCurve::Linear { step: u64::MAX }, 1_000));
+
+        let tight = FeeCurveBounds {
+            max_linear_step_bps: 500,
+            ..B
+        };
+        assert!(tight.allows(&FeeCurve::Linear { step: 50 }, 1_000));
+        assert!(!tight.allows(&FeeCurve::Linear { step: 51 }, 1_000));
+        assert!(tight.allows(&FeeCurve::Flat, 1_000));
+    }
+
+    #[test]
+    fn time_decay_floor_is_bounded() {
+        let decay = |floor_bps| FeeCurve::TimeDecay {
+            ratio_bps: 12_000,
+            decay_bps: 500,
+            period_secs: 86_400,
+            floor_bps,
+        };
+        assert!(!B.allows(&decay(0), 1_000));
+        assert!(!B.allows(&decay(999), 1_000));
+        assert!(B.allows(&decay(1_000), 1_000));
+        assert!(B.allows(&decay(10_000), 1_000));
+        assert!(!B.allows(&decay(10_001), 1_000));
+    }
+
+    #[test]
+    fn bounds_need_a_non_zero_floor() {
+        assert!(B.is_valid());
+        assert!(!FeeCurveBounds { min_floor_bps: 0, ..B }.is_valid());
+        assert!(!FeeCurveBounds {
+            min_floor_bps: 10_001,
+            ..B
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn bounds_need_a_decay_period() {
+        assert!(!FeeCurveBounds { min_period_secs: 0, ..B }.is_valid());
+        assert!(FeeCurveBounds { min_period_secs: 1, ..B }.is_valid());
+    }
+
+    #[test]
+    fn bounds_cap_the_linear_step() {
+        assert!(FeeCurveBounds {
+            max_linear_step_bps: 10_000,
+            ..B
+        }
+        .is_valid());
+        assert!(!FeeCurveBounds {
+            max_linear_step_bps: 10_001,
+            ..B
+        }
+        .is_valid());
+        assert!(!FeeCurveBounds {
+            max_linear_step_bps: u16::MAX,
+            ..B
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn ladder_fee_matches_stepping_one_attempt_at_a_time() {
+        let curves = [
+            DEFAULT_FEE_CURVE,
+            FeeCurve::Linear { step: 7 },
+            FeeCurve::Flat,
+            FeeCurve::CappedGeometric {
+                ratio_bps: 15_000,
+                cap: 40_000,
+            },
+        ];
+        for curve in curves {
+            for start in [0, 1, 999] {
+                let mut fee = start;
+                for i in 0..300 {
+                    assert_eq!(ladder_fee(&curve, start, 1_000_000, i), fee, "{curve:?} from {start}, attempt {i}");
+                    fee = next_ladder_fee(&curve, fee, 1_000_000);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn next_ladder_fee_saturates_at_max_fee() {
+        let geometric = FeeCurve::Geometric { ratio_bps: 20_000 };
+        assert_eq!(next_ladder_fee(&geometric, 600, 1_000), 1_000);
+        assert_eq!(next_ladder_fee(&geometric, 1_000, 1_000), 1_000);
+        assert_eq!(next_ladder_fee(&FeeCurve::Linear { step: 300 }, 900, 1_000), 1_000);
+        assert_eq!(ladder_fee(&geometric, 1, 1_000, 1_000_000), 1_000);
+    }
+
+    #[test]
+    fn next_ladder_fee_saturates_at_u64_max() {
+        let geometric = FeeCurve::Geometric { ratio_bps: 20_000 };
+        assert_eq!(next_ladder_fee(&geometric, u64::MAX / 2 + 1, u64::MAX), u64::MAX);
+        assert_eq!(next_ladder_fee(&geometric, u64::MAX, u64::MAX), u64::MAX);
+        assert_eq!(next_ladder_fee(&FeeCurve::Linear { step: u64::MAX }, 2, u64::MAX), u64::MAX);
+        assert_eq!(ladder_fee(&DEFAULT_FEE_CURVE, 1, u64::MAX, u64::MAX), u64::MAX);
+    }
+}
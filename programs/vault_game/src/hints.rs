@@ -0,0 +1,129 @@
+//! Verifiable Mastermind-style hints.
+//!
+//! At `create_vault` a creator may commit to a Merkle root over per-position digit leaves
+//! `sha256("vault_game:hint_leaf" || position || digit || salt)`, one 32-byte salt per position.
+//! Feedback on a guess is proven by revealing the relevant leaves plus their proofs (`submit_hint`).
+//!
+//! `build_tree` / `proof` are meant for creators and referees off-chain; `verify_proof` runs on-chain.
+//! Digits are numeric values 0–9 (not ASCII).
+//!
+//! A revealed leaf makes its position's digit public; `submit_hint` limits which leaves may be
+//! revealed, and `reveal_salt` checks the root against the PIN once the vault has expired.
+
+use solana_sha256_hasher::hashv;
+
+const LEAF_TAG: &[u8] = b"vault_game:hint_leaf";
+const NODE_TAG: &[u8] = b"vault_game:hint_node";
+
+pub fn leaf(position: u8, digit: u8, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[LEAF_TAG, &[position], &[digit], salt]).to_bytes()
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_TAG, left, right]).to_bytes()
+}
+
+/// Number of siblings in a proof for a PIN of `pin_len` digits.
+pub fn depth(pin_len: usize) -> usize {
+    pin_len.max(1).next_power_of_two().trailing_zeros() as usize
+}
+
+/// All tree levels, leaves first. Leaves are padded with zero hashes up to a power of two.
+///
+/// Panics if `digits` and `salts` differ in length.
+pub fn build_tree(digits: &[u8], salts: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    assert_eq!(digits.len(), salts.len(), "one salt per digit");
+
+    let mut level: Vec<[u8; 32]> = digits
+        .iter()
+        .zip(salts)
+        .enumerate()
+        .map(|(i, (d, s))| leaf(i as u8, *d, s))
+        .collect();
+    level.resize(digits.len().max(1).next_power_of_two(), [0u8; 32]);
+
+    let mut levels = vec![level];
+    while let Some(top) = levels.last().filter(|l| l.len() > 1) {
+        let next = top.chunks(2).map(|pair| node(&pair[0], &pair[1])).collect();
+        levels.push(next);
+    }
+    levels
+}
+
+pub fn root(levels: &[Vec<[u8; 32]>]) -> [u8; 32] {
+    levels.last().map(|l| l[0]).unwrap_or_default()
+}
+
+/// Sibling hashes from the leaf at `position` up to (excluding) the root.
+pub fn proof(levels: &[Vec<[u8; 32]>], position: usize) -> Vec<[u8; 32]> {
+    let mut idx = position;
+    levels[..levels.len().saturating_sub(1)]
+        .iter()
+        .map(|level| {
+            let sibling = level[idx ^ 1];
+            idx >>= 1;
+            sibling
+        })
+        .collect()
+}
+
+/// Check that `(position, digit, salt)` is a leaf under `root`.
+pub fn verify_proof(root: &[u8; 32], position: u8, digit: u8, salt: &[u8; 32], siblings: &[[u8; 32]]) -> bool {
+    let mut acc = leaf(position, digit, salt);
+    let mut idx = position as usize;
+    for sibling in siblings {
+        acc = if idx & 1 == 0 { node(&acc, sibling) } else { node(sibling, &acc) };
+        idx >>= 1;
+    }
+    idx == 0 && acc == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn salts(n: usize) -> Vec<[u8; 32]> {
+        (0..n).map(|i| [i as u8 + 1; 32]).collect()
+    }
+
+    #[test]
+    fn every_leaf_verifies_for_every_pin_length() {
+        for pin_len in 1..=6 {
+            let digits: Vec<u8> = (0..pin_len as u8).map(|i| (i * 7 + 3) % 10).collect();
+            let salts = salts(pin_len);
+            let levels = build_tree(&digits, &salts);
+            let root = root(&levels);
+
+            for (pos, digit) in digits.iter().enumerate() {
+                let siblings = proof(&levels, pos);
+                assert_eq!(siblings.len(), depth(pin_len));
+                assert!(verify_proof(&root, pos as u8, *digit, &salts[pos], &siblings));
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_digit_salt_or_position_is_rejected() {
+        let digits = [4, 8, 2, 1, 9];
+        let salts = salts(digits.len());
+        let levels = build_tree(&digits, &salts);
+        let root = root(&levels);
+        let siblings = proof(&levels, 2);
+
+        assert!(verify_proof(&root, 2, 2, &salts[2], &siblings));
+        assert!(!verify_proof(&root, 2, 3, &salts[2], &siblings));
+        assert!(!verify_proof(&root, 2, 2, &salts[1], &siblings));
+        assert!(!verify_proof(&root, 3, 2, &salts[2], &siblings));
+        // Padding leaves and out-of-range positions never verify.
+        assert!(!verify_proof(&root, 5, 0, &[0; 32], &proof(&levels, 5)));
+        assert!(!verify_proof(&root, 10, 2, &salts[2], &siblings));
+    }
+
+    #[test]
+    fn root_depends_on_every_digit() {
+        let salts = salts(4);
+        let a = root(&build_tree(&[1, 2, 3, 4], &salts));
+        let b = root(&build_tree(&[1, 2, 3, 5], &salts));
+        assert_ne!(a, b);
+    }
+}
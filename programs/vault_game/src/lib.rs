@@ -1,9 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
-    token_interface::{self, Mint as IMint, TokenAccount as ITokenAccount, TokenInterface, TransferChecked},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{self, CloseAccount, Mint as IMint, TokenAccount as ITokenAccount, TokenInterface, TransferChecked},
 };
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::ZeroCopy;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+
+pub mod access;
+pub mod fees;
+pub mod hints;
+
+use fees::{FeeCurve, FeeCurveBounds, FeeParts, FeeSplit, FeeSplitBounds};
 
 declare_id!("B1uj973FayJZYCHVJx3td57zMMBzg4n6UENB3bS24F3t");
 
@@ -14,6 +22,43 @@ const SCORE_PER_ATTEMPT: u64 = 1;
 const SCORE_PER_VAULT_CREATED: u64 = 50;
 const SCORE_PER_WIN: u64 = 250;
 
+// -----------------
+// Claims (commit/reveal)
+// -----------------
+/// Minimum slots between `commit_claim` and the matching `claim_win` reveal.
+/// Long enough that a copied secret cannot be committed + revealed before the original lands.
+const CLAIM_REVEAL_DELAY_SLOTS: u64 = 8;
+
+/// Seconds after `reveal_salt` during which players reveal claims on salted claim-mode vaults.
+/// Payouts wait for it to close, since an older commitment can still displace the winner.
+const SALTED_CLAIM_WINDOW_SECS: i64 = 86_400;
+
+/// Seconds after its challenge vault expires that a mega winner has to `claim_mega_vault*` before
+/// `roll_mega_season` may roll the season over unclaimed.
+const MEGA_CLAIM_TIMEOUT_SECS: i64 = 30 * 86_400;
+
+/// Basis-point denominator for percentage settings.
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// -----------------
+// Emergency pause (`GlobalConfig.paused` bits)
+// -----------------
+/// `create_vault*`, `add_reward`, `set_vault_metadata`.
+pub const PAUSE_CREATE: u8 = 1 << 0;
+/// `make_guess_*`, `submit_hint`.
+pub const PAUSE_GUESS: u8 = 1 << 1;
+/// `commit_claim`, `claim_win`, `submit_verdict`.
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+/// Pooled payouts: `claim_mega_vault*`, `roll_mega_season`, treasury withdrawals.
+pub const PAUSE_PAYOUT: u8 = 1 << 3;
+/// Withdrawals from individual vaults (`claim_prize*`, `reclaim_prize*`, rewards, `cancel_vault`), and
+/// `reveal_salt`, which salted vaults need before either payout.
+/// Separate from `PAUSE_PAYOUT` so settled players' funds stay withdrawable during an incident.
+pub const PAUSE_WITHDRAW: u8 = 1 << 4;
+
+/// Max creator-declared associate wallets barred from winning a vault.
+const MAX_EXCLUDED_WALLETS: usize = 8;
+
 // -----------------
 // Program
 // -----------------
@@ -34,14 +79,313 @@ pub mod vault_game {
     }
 
     /// Admin-only: set the globally-visible Mega Vault "challenge" vault.
+    ///
+    /// Also records it as the challenge of the open mega season for the vault's fee currency.
     pub fn set_mega_challenge_vault(ctx: Context<SetMegaChallengeVault>, vault: Pubkey) -> Result<()> {
-        require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.global_state.authority, VaultError::NotAuthorized);
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(
+            ctx.accounts.challenge_vault.status == VaultStatus::Active as u8,
+            VaultError::VaultNotActive
+        );
 
         let mc = &mut ctx.accounts.mega_challenge;
-        mc.authority = ctx.accounts.authority.key();
         mc.vault = vault;
         mc.bump = ctx.bumps.mega_challenge;
 
+        ctx.accounts.mega_season.challenge_vault = Some(vault);
+
+        Ok(())
+    }
+
+    /// Admin-only: open season 0 of the mega pool for `fee_mint` (`Pubkey::default()` = SOL pool).
+    ///
+    /// The opening balance is whatever the pool already holds. Later seasons are opened
+    /// automatically when a season is won (`claim_mega_vault*`) or rolled over (`roll_mega_season`).
+    pub fn open_mega_season(ctx: Context<OpenMegaSeason>, fee_mint: Pubkey) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let opening_balance = if fee_mint == Pubkey::default() {
+            lamports_above_rent(&ctx.accounts.mega_vault.to_account_info())?
+        } else {
+            ctx.accounts
+                .mega_vault_fee_ata
+                .as_ref()
+                .ok_or(VaultError::WrongFeeMint)?
+                .amount
+        };
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .mega_season
+            .open(fee_mint, 0, opening_balance, now, ctx.bumps.mega_season);
+
+        emit!(MegaSeasonOpened {
+            fee_mint,
+            season_id: 0,
+            opening_balance,
+        });
+
+        Ok(())
+    }
+
+    /// Roll an open mega season whose challenge vault expired uncracked into the next season, or
+    /// whose winner left the jackpot unclaimed for `MEGA_CLAIM_TIMEOUT_SECS` past expiry.
+    ///
+    /// Permissionless; the caller pays rent for the next season account.
+    pub fn roll_mega_season(ctx: Context<RollMegaSeason>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_PAYOUT)?;
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.challenge_vault;
+        let season = &mut ctx.accounts.mega_season;
+
+        require!(season.challenge_vault == Some(vault.key()), VaultError::NotMegaChallengeVault);
+        vault.check_mega_rollover(now)?;
+
+        let closing_balance = season.jackpot()?;
+        season.status = MegaSeasonStatus::RolledOver as u8;
+        season.closing_balance = closing_balance;
+        season.closed_at = Some(now);
+
+        let (fee_mint, next_id) = (season.fee_mint, season.season_id + 1);
+        ctx.accounts
+            .next_season
+            .open(fee_mint, next_id, closing_balance, now, ctx.bumps.next_season);
+
+        emit!(MegaSeasonOpened {
+            fee_mint,
+            season_id: next_id,
+            opening_balance: closing_balance,
+        });
+
+        Ok(())
+    }
+
+    /// Admin-only: register the referee key whose Ed25519 verdicts can settle referee-mode vaults.
+    pub fn set_referee(ctx: Context<SetGlobalConfig>, referee: Pubkey) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.referee = referee;
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Admin-only: share of the mega pool (basis points) paid to the mega challenge winner.
+    pub fn set_mega_payout_bps(ctx: Context<SetGlobalConfig>, bps: u16) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(bps as u64 <= BPS_DENOMINATOR, VaultError::BadBps);
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.mega_payout_bps = Some(bps);
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Admin-only: register or update an accepted SPL fee mint.
+    ///
+    /// Disabling a mint blocks new vaults and guesses in it; existing vaults can still settle.
+    pub fn set_fee_mint_config(
+        ctx: Context<SetFeeMintConfig>,
+        enabled: bool,
+        min_prize_amount: u64,
+        min_base_fee_amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let fmc = &mut ctx.accounts.fee_mint_config;
+        fmc.mint = ctx.accounts.fee_mint.key();
+        fmc.enabled = enabled;
+        fmc.min_prize_amount = min_prize_amount;
+        fmc.min_base_fee_amount = min_base_fee_amount;
+        fmc.decimals = ctx.accounts.fee_mint.decimals;
+        fmc.bump = ctx.bumps.fee_mint_config;
+
+        let ms = &mut ctx.accounts.mint_stats;
+        ms.mint = fmc.mint;
+        ms.bump = ctx.bumps.mint_stats;
+
+        Ok(())
+    }
+
+    /// Admin-only: limits on creator-chosen fee curves (see `fees::FeeCurveBounds`).
+    pub fn set_fee_curve_bounds(ctx: Context<SetGlobalConfig>, bounds: FeeCurveBounds) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(bounds.is_valid(), VaultError::BadFeeCurve);
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.fee_curve_bounds = Some(bounds);
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Admin-only: default `max_fee_amount` for vaults created without one (`None` = uncapped).
+    pub fn set_default_max_fee(ctx: Context<SetGlobalConfig>, max_fee_amount: Option<u64>) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.default_max_fee_amount = max_fee_amount;
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Admin-only: global attempt fee split and the bounds for per-vault overrides.
+    ///
+    /// Applies to vaults created afterwards; each vault keeps the split it was created with.
+    pub fn set_fee_split(ctx: Context<SetGlobalConfig>, split: FeeSplit, bounds: FeeSplitBounds) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(bounds.allows(&split), VaultError::BadFeeSplit);
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.fee_split = Some(split);
+        gc.fee_split_bounds = Some(bounds);
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Admin-only: create `GlobalStats` and the SOL `MintStats`.
+    ///
+    /// Totals start from zero here; SPL `MintStats` are created by `set_fee_mint_config`.
+    pub fn init_global_stats(ctx: Context<InitGlobalStats>) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        ctx.accounts.global_stats.bump = ctx.bumps.global_stats;
+        let ms = &mut ctx.accounts.sol_mint_stats;
+        ms.mint = Pubkey::default();
+        ms.bump = ctx.bumps.sol_mint_stats;
+
+        Ok(())
+    }
+
+    /// Admin-only: create the protocol treasury PDA that receives the treasury fee share.
+    ///
+    /// SOL is held by the PDA itself; SPL shares go to its ATA for each fee mint.
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        ctx.accounts.treasury.bump = ctx.bumps.treasury;
+
+        Ok(())
+    }
+
+    /// Admin-only: withdraw SOL held by the treasury above its rent-exempt minimum.
+    pub fn withdraw_treasury_sol(ctx: Context<WithdrawTreasurySol>, amount: u64) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_PAYOUT)?;
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(
+            amount <= lamports_above_rent(&ctx.accounts.treasury.to_account_info())?,
+            VaultError::InsufficientTreasury
+        );
+
+        ctx.accounts.treasury.sub_lamports(amount)?;
+        ctx.accounts.authority.add_lamports(amount)?;
+
+        Ok(())
+    }
+
+    /// Admin-only: withdraw SPL fee tokens from the treasury's ATA.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_PAYOUT)?;
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let bump = ctx.accounts.treasury.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[bump]]];
+        transfer_fee_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.fee_mint,
+            ctx.accounts.treasury_fee_ata.to_account_info(),
+            &mut ctx.accounts.authority_fee_ata,
+            ctx.accounts.treasury.to_account_info(),
+            signer_seeds,
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Admin-only: share of each attempt's mega cut (basis points) paid to the player's referrer.
+    pub fn set_referral_bps(ctx: Context<SetGlobalConfig>, bps: u16) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+        require!(bps as u64 <= BPS_DENOMINATOR, VaultError::BadBps);
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.referral_bps = Some(bps);
+        gc.bump = ctx.bumps.global_config;
+
+        Ok(())
+    }
+
+    /// Bind the player's referrer. One-time: the link cannot be changed afterwards.
+    ///
+    /// The referrer must already have a `PlayerProfile`.
+    pub fn bind_referrer(ctx: Context<BindReferrer>, referrer: Pubkey) -> Result<()> {
+        require!(referrer != ctx.accounts.player.key(), VaultError::BadReferrer);
+
+        let pp = &mut ctx.accounts.player_profile;
+        require!(pp.referrer.is_none(), VaultError::ReferrerAlreadyBound);
+        pp.authority = ctx.accounts.player.key();
+        pp.referrer = Some(referrer);
+        pp.last_seen_ts = Clock::get()?.unix_timestamp;
+        pp.bump = ctx.bumps.player_profile;
+
+        emit!(ReferrerBound {
+            player: ctx.accounts.player.key(),
+            referrer,
+        });
+
+        Ok(())
+    }
+
+    /// Admin-only: step 1 of authority rotation. Proposing again replaces the pending authority.
+    pub fn propose_authority(ctx: Context<SetGlobalConfig>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.pending_authority = Some(new_authority);
+        gc.bump = ctx.bumps.global_config;
+
+        emit!(AuthorityProposed {
+            authority: ctx.accounts.global_state.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Step 2 of authority rotation: the pending authority signs to take over.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let new_authority = ctx.accounts.new_authority.key();
+        require!(
+            ctx.accounts.global_config.pending_authority == Some(new_authority),
+            VaultError::NotPendingAuthority
+        );
+
+        let previous = ctx.accounts.global_state.authority;
+        ctx.accounts.global_state.authority = new_authority;
+        ctx.accounts.global_config.pending_authority = None;
+
+        emit!(AuthorityChanged {
+            previous,
+            authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Admin-only: set the emergency pause bitmask (`PAUSE_*`); 0 resumes everything.
+    pub fn set_paused(ctx: Context<SetGlobalConfig>, paused: u8) -> Result<()> {
+        ctx.accounts.global_state.require_admin(&ctx.accounts.authority)?;
+
+        let gc = &mut ctx.accounts.global_config;
+        gc.paused = paused;
+        gc.bump = ctx.bumps.global_config;
+
+        emit!(PauseUpdated { paused });
+
         Ok(())
     }
 
@@ -59,10 +403,11 @@ pub mod vault_game {
 
     /// Create a new vault.
     ///
-    /// Fees are SKR by default; SOL is allowed as fallback.
-    /// - If `fee_mint` is Some(mint): fees are paid in that SPL token (must equal GlobalState.skr_mint in v1).
-    /// - If `fee_mint` is None: fees are paid in SOL.
+    /// Fees are SKR by default; SOL vaults are created with `create_vault_sol`.
+    /// - `fee_mint` must be Some(mint): fees are paid in that SPL token, which must be enabled in
+    ///   the fee-mint registry (`set_fee_mint_config`) and meet its prize / base-fee minimums.
     pub fn create_vault(ctx: Context<CreateVault>, args: CreateVaultArgs) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CREATE)?;
         require!(args.end_ts > Clock::get()?.unix_timestamp, VaultError::BadEndTs);
         // Allow zero-fee vaults for free-to-play/demo mode.
         // (u64 is always >= 0)
@@ -70,9 +415,6 @@ pub mod vault_game {
         let gs = &mut ctx.accounts.global_state;
         let vault = &mut ctx.accounts.vault;
 
-        // Record vault id used for PDA signing.
-        vault.vault_id = gs.vault_count;
-
         // Touch player profile (init if needed) + record creation.
         let pp = &mut ctx.accounts.player_profile;
         pp.authority = ctx.accounts.creator.key();
@@ -81,73 +423,135 @@ pub mod vault_game {
         pp.last_seen_ts = Clock::get()?.unix_timestamp;
         pp.bump = ctx.bumps.player_profile;
 
-        // v1 vaults are token vaults (SKR). SOL is kept as fallback for later.
-        // v1: token vaults only (SKR)
-        require!(args.fee_mint.is_some(), VaultError::PrizeRequiresMint);
-        require_keys_eq!(ctx.accounts.fee_mint.key(), gs.skr_mint, VaultError::UnsupportedFeeMint);
+        // Token vaults only on this path; the mint must be registered and enabled.
+        require!(args.fee_mint == Some(ctx.accounts.fee_mint.key()), VaultError::PrizeRequiresMint);
+        ctx.accounts.fee_mint_config.check_new_vault(&ctx.accounts.fee_mint, &args)?;
         vault.fee_mint = ctx.accounts.fee_mint.key();
         vault.is_sol_fee = false;
 
-        // Prize lock rules (SKR)
+        init_vault(
+            vault,
+            &args,
+            ctx.accounts.creator.key(),
+            gs.vault_count,
+            ctx.bumps.vault,
+            &ctx.accounts.global_config,
+        )?;
+        match (args.metadata.clone(), ctx.accounts.vault_metadata.as_mut()) {
+            (Some(metadata), Some(account)) => {
+                let bump = ctx.bumps.vault_metadata.ok_or(VaultError::MissingVaultMetadata)?;
+                write_vault_metadata(vault, account, metadata, bump)?;
+            }
+            (None, None) => {}
+            (Some(_), None) => return err!(VaultError::MissingVaultMetadata),
+            (None, Some(_)) => return err!(VaultError::UnexpectedVaultMetadata),
+        }
+
+        // Lock prize into vault_prize_ata; record what arrived net of any transfer fee.
         if args.prize_amount > 0 {
-            require!(args.prize_amount >= 1000, VaultError::PrizeTooSmall);
+            vault.prize_amount = transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.creator_fee_ata.to_account_info(),
+                &mut ctx.accounts.vault_prize_ata,
+                ctx.accounts.creator.to_account_info(),
+                &[],
+                args.prize_amount,
+            )?;
         }
 
-        vault.creator = ctx.accounts.creator.key();
-        vault.status = VaultStatus::Active as u8;
-        vault.created_at = Clock::get()?.unix_timestamp;
-        vault.end_ts = args.end_ts;
-        vault.secret_hash = args.secret_hash;
-        vault.prize_amount = args.prize_amount;
-        vault.paid_out = false;
-
-        // Guess fee ladder (attempts-only): fee increases 1.2x each attempt.
-        // Starting fee is derived from creator base fee and PIN length.
-        // v1: standard vaults are 3–6 digits. Mega vault uses 8 digits.
-        require!((3..=6).contains(&args.pin_len) || args.pin_len == 8, VaultError::BadPinLen);
-
-        let mult: u64 = match args.pin_len {
-            3 => 100,
-            4 => 25,
-            5 => 10,
-            6 => 10,
-            8 => 1,
-            _ => 10,
-        };
+        index_new_vault(
+            &ctx.accounts.vault_index,
+            ctx.bumps.vault_index,
+            &ctx.accounts.creator_index,
+            ctx.bumps.creator_index,
+            vault,
+            pp.vaults_created - 1,
+        )?;
 
-        let starting = if args.base_fee_amount == 0 {
-            0
-        } else {
-            args.base_fee_amount
-                .checked_mul(mult)
-                .ok_or(VaultError::MathOverflow)?
-        };
+        gs.vault_count = gs.vault_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        ctx.accounts.global_stats.record_vault_created();
+
+        emit!(VaultCreated {
+            vault: vault.key(),
+            creator: vault.creator,
+            end_ts: vault.end_ts,
+            is_sol_fee: vault.is_sol_fee,
+            fee_mint: vault.fee_mint,
+            guess_fee_amount: vault.starting_fee_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create a new SOL vault: prize and guess fees are lamports held by the Vault PDA.
+    ///
+    /// `fee_mint` must be None. Settle with `claim_prize_sol` / `reclaim_prize_sol`.
+    pub fn create_vault_sol(ctx: Context<CreateVaultSol>, args: CreateVaultArgs) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CREATE)?;
+        require!(args.end_ts > Clock::get()?.unix_timestamp, VaultError::BadEndTs);
+        require!(args.fee_mint.is_none(), VaultError::WrongFeeCurrency);
+
+        let gs = &mut ctx.accounts.global_state;
+        let vault = &mut ctx.accounts.vault;
+
+        // Touch player profile (init if needed) + record creation.
+        let pp = &mut ctx.accounts.player_profile;
+        pp.authority = ctx.accounts.creator.key();
+        pp.vaults_created = pp.vaults_created.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        pp.score = pp.score.checked_add(SCORE_PER_VAULT_CREATED).ok_or(VaultError::MathOverflow)?;
+        pp.last_seen_ts = Clock::get()?.unix_timestamp;
+        pp.bump = ctx.bumps.player_profile;
 
-        vault.starting_fee_amount = starting;
-        vault.current_fee_amount = starting;
-        vault.attempt_count = 0;
+        vault.fee_mint = Pubkey::default();
+        vault.is_sol_fee = true;
 
-        vault.total_fees_collected = 0;
-        vault.winner_fee_pool = 0;
+        init_vault(
+            vault,
+            &args,
+            ctx.accounts.creator.key(),
+            gs.vault_count,
+            ctx.bumps.vault,
+            &ctx.accounts.global_config,
+        )?;
+        match (args.metadata.clone(), ctx.accounts.vault_metadata.as_mut()) {
+            (Some(metadata), Some(account)) => {
+                let bump = ctx.bumps.vault_metadata.ok_or(VaultError::MissingVaultMetadata)?;
+                write_vault_metadata(vault, account, metadata, bump)?;
+            }
+            (None, None) => {}
+            (Some(_), None) => return err!(VaultError::MissingVaultMetadata),
+            (None, Some(_)) => return err!(VaultError::UnexpectedVaultMetadata),
+        }
 
-        // Lock prize (SKR) into vault_prize_ata.
+        // Lock prize (lamports) into the Vault PDA, on top of its rent-exempt balance.
         if args.prize_amount > 0 {
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi = CpiContext::new(
-                cpi_program,
-                Transfer {
-                    from: ctx.accounts.creator_fee_ata.to_account_info(),
-                    to: ctx.accounts.vault_prize_ata.to_account_info(),
-                    authority: ctx.accounts.creator.to_account_info(),
-                },
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.creator.key(),
+                &vault.key(),
+                args.prize_amount,
             );
-            token::transfer(cpi, args.prize_amount)?;
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    ctx.accounts.creator.to_account_info(),
+                    vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
         }
-        vault.winner = None;
-        vault.settled_at = None;
-        vault.bump = ctx.bumps.vault;
+
+        index_new_vault(
+            &ctx.accounts.vault_index,
+            ctx.bumps.vault_index,
+            &ctx.accounts.creator_index,
+            ctx.bumps.creator_index,
+            vault,
+            pp.vaults_created - 1,
+        )?;
 
         gs.vault_count = gs.vault_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        ctx.accounts.global_stats.record_vault_created();
 
         emit!(VaultCreated {
             vault: vault.key(),
@@ -162,10 +566,17 @@ pub mod vault_game {
     }
 
     /// Make a guess (SOL fallback path).
-    pub fn make_guess_sol(ctx: Context<MakeGuessSol>) -> Result<()> {
+    ///
+    /// `guess` is the player's hashed/encrypted PIN guess, stored in this attempt's `GuessRecord`.
+    /// On hint-enabled vaults it must be `sha256(digits || player || nonce)` so `submit_hint` can open it.
+    /// `allowlist_proof` is only read on allowlist vaults (see `access`); token-gated vaults need
+    /// `gate_token_account`.
+    pub fn make_guess_sol(ctx: Context<MakeGuessSol>, guess: [u8; 32], allowlist_proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_GUESS)?;
         let vault = &mut ctx.accounts.vault;
         require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
         require!(Clock::get()?.unix_timestamp <= vault.end_ts, VaultError::VaultExpired);
+        vault.check_access(&ctx.accounts.player.key(), ctx.accounts.gate_token_account.as_deref(), &allowlist_proof)?;
         require!(vault.is_sol_fee, VaultError::WrongFeeCurrency);
 
         // Touch player profile (init if needed) + record attempt.
@@ -176,7 +587,34 @@ pub mod vault_game {
         pp.last_seen_ts = Clock::get()?.unix_timestamp;
         pp.bump = ctx.bumps.player_profile;
 
-        let fee = vault.current_fee_amount;
+        let fee = vault.current_fee(Clock::get()?.unix_timestamp);
+
+        // Per-player attempt ledger for this vault; gates `claim_win` and enforces the vault's limits.
+        let ar = &mut ctx.accounts.attempt_record;
+        let now = Clock::get()?.unix_timestamp;
+        vault.check_attempt_limits(ar, now)?;
+        ar.vault = vault.key();
+        ar.player = ctx.accounts.player.key();
+        ar.attempts = ar.attempts.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        ar.fees_paid = ar.fees_paid.checked_add(fee).ok_or(VaultError::MathOverflow)?;
+        ar.last_attempt_slot = Clock::get()?.slot;
+        ar.last_attempt_ts = now;
+        ar.bump = ctx.bumps.attempt_record;
+
+        // Authoritative per-attempt history.
+        let attempt_index = vault.attempt_count;
+        let gr = &mut ctx.accounts.guess_record;
+        gr.vault = vault.key();
+        gr.player = ctx.accounts.player.key();
+        gr.attempt_index = attempt_index;
+        gr.guess = guess;
+        gr.fee_paid = fee;
+        gr.slot = ar.last_attempt_slot;
+        gr.feedback = GuessFeedback::default();
+        gr.bump = ctx.bumps.guess_record;
+
+        ctx.accounts.global_stats.record_attempt();
+
         if fee == 0 {
             // Free-to-play attempt: no transfers.
             vault.attempt_count = vault.attempt_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
@@ -184,74 +622,91 @@ pub mod vault_game {
             emit!(GuessMade {
                 vault: vault.key(),
                 player: ctx.accounts.player.key(),
+                attempt_index,
                 fee: 0,
                 winner_cut: 0,
                 mega_cut: 0,
+                treasury_cut: 0,
+                creator_cut: 0,
+                referrer: None,
+                referral_cut: 0,
             });
             return Ok(());
         }
 
-        let (winner_cut, mega_cut) = split_fee(fee)?;
-
-        // 80% -> mega_vault PDA, 20% -> vault PDA (kept for winner payout)
-        let ix1 = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.player.key(),
-            &ctx.accounts.mega_vault.key(),
-            mega_cut,
-        );
-        anchor_lang::solana_program::program::invoke(
-            &ix1,
-            &[
-                ctx.accounts.player.to_account_info(),
-                ctx.accounts.mega_vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+        let mut parts = vault.fee_split.split(fee);
+
+        // Referral cut comes out of the mega share.
+        let referral_cut = referral_cut_for(&ctx.accounts.player_profile, &ctx.accounts.global_config, parts.mega);
+        parts.mega -= referral_cut;
+        if referral_cut > 0 {
+            let referrer = ctx.accounts.referrer.as_ref().ok_or(VaultError::MissingReferrer)?;
+            pay_lamports(
+                &ctx.accounts.player.to_account_info(),
+                &referrer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                referral_cut,
+            )?;
+            let rp = ctx.accounts.referrer_profile.as_mut().ok_or(VaultError::MissingReferrer)?;
+            rp.referral_earnings_sol = rp.referral_earnings_sol.checked_add(referral_cut).ok_or(VaultError::MathOverflow)?;
+        }
 
-        let ix2 = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.player.key(),
-            &vault.key(),
-            winner_cut,
-        );
-        anchor_lang::solana_program::program::invoke(
-            &ix2,
-            &[
-                ctx.accounts.player.to_account_info(),
-                vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+        ctx.accounts.mega_season.book_fee_inflow(parts.mega)?;
 
-        vault.total_fees_collected = vault
-            .total_fees_collected
-            .checked_add(fee)
-            .ok_or(VaultError::MathOverflow)?;
-        vault.winner_fee_pool = vault
-            .winner_fee_pool
-            .checked_add(winner_cut)
-            .ok_or(VaultError::MathOverflow)?;
+        // Winner share stays in the vault PDA for payout; the rest goes to mega vault, treasury, creator.
+        let player = ctx.accounts.player.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        pay_lamports(&player, &ctx.accounts.mega_vault.to_account_info(), &system_program, parts.mega)?;
+        pay_lamports(&player, &vault.to_account_info(), &system_program, parts.winner)?;
+        if parts.treasury > 0 {
+            let treasury = ctx.accounts.treasury.as_ref().ok_or(VaultError::MissingFeeRecipient)?;
+            pay_lamports(&player, &treasury.to_account_info(), &system_program, parts.treasury)?;
+        }
+        if parts.creator > 0 {
+            let creator = ctx.accounts.vault_creator.as_ref().ok_or(VaultError::MissingFeeRecipient)?;
+            pay_lamports(&player, &creator.to_account_info(), &system_program, parts.creator)?;
+        }
+        let FeeParts {
+            winner: winner_cut,
+            mega: mega_cut,
+            treasury: treasury_cut,
+            creator: creator_cut,
+        } = parts;
 
-        vault.attempt_count = vault.attempt_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
-        vault.current_fee_amount = next_fee(vault.current_fee_amount)?;
+        vault.record_paid_attempt(fee, winner_cut)?;
+        ctx.accounts.mint_stats.record_fee(fee, mega_cut);
 
         emit!(GuessMade {
             vault: vault.key(),
             player: ctx.accounts.player.key(),
+            attempt_index,
             fee,
             winner_cut,
             mega_cut,
+            treasury_cut,
+            creator_cut,
+            referrer: ctx.accounts.player_profile.referrer.filter(|_| referral_cut > 0),
+            referral_cut,
         });
 
         Ok(())
     }
 
-    /// Make a guess (SPL token path, SKR in v1).
-    pub fn make_guess_spl(ctx: Context<MakeGuessSpl>) -> Result<()> {
+    /// Make a guess (SPL token path, any enabled registry mint).
+    ///
+    /// `guess` is the player's hashed/encrypted PIN guess, stored in this attempt's `GuessRecord`.
+    /// On hint-enabled vaults it must be `sha256(digits || player || nonce)` so `submit_hint` can open it.
+    /// `allowlist_proof` is only read on allowlist vaults (see `access`); token-gated vaults need
+    /// `gate_token_account`.
+    pub fn make_guess_spl(ctx: Context<MakeGuessSpl>, guess: [u8; 32], allowlist_proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_GUESS)?;
         let vault = &mut ctx.accounts.vault;
         require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
         require!(Clock::get()?.unix_timestamp <= vault.end_ts, VaultError::VaultExpired);
+        vault.check_access(&ctx.accounts.player.key(), ctx.accounts.gate_token_account.as_deref(), &allowlist_proof)?;
         require!(!vault.is_sol_fee, VaultError::WrongFeeCurrency);
         require_keys_eq!(ctx.accounts.fee_mint.key(), vault.fee_mint, VaultError::WrongFeeMint);
+        require!(ctx.accounts.fee_mint_config.enabled, VaultError::UnsupportedFeeMint);
 
         // Touch player profile (init if needed) + record attempt.
         let pp = &mut ctx.accounts.player_profile;
@@ -261,80 +716,263 @@ pub mod vault_game {
         pp.last_seen_ts = Clock::get()?.unix_timestamp;
         pp.bump = ctx.bumps.player_profile;
 
-        let fee = vault.current_fee_amount;
+        let fee = vault.current_fee(Clock::get()?.unix_timestamp);
+
+        // Per-player attempt ledger for this vault; gates `claim_win` and enforces the vault's limits.
+        let ar = &mut ctx.accounts.attempt_record;
+        let now = Clock::get()?.unix_timestamp;
+        vault.check_attempt_limits(ar, now)?;
+        ar.vault = vault.key();
+        ar.player = ctx.accounts.player.key();
+        ar.attempts = ar.attempts.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        ar.fees_paid = ar.fees_paid.checked_add(fee).ok_or(VaultError::MathOverflow)?;
+        ar.last_attempt_slot = Clock::get()?.slot;
+        ar.last_attempt_ts = now;
+        ar.bump = ctx.bumps.attempt_record;
+
+        // Authoritative per-attempt history.
+        let attempt_index = vault.attempt_count;
+        let gr = &mut ctx.accounts.guess_record;
+        gr.vault = vault.key();
+        gr.player = ctx.accounts.player.key();
+        gr.attempt_index = attempt_index;
+        gr.guess = guess;
+        gr.fee_paid = fee;
+        gr.slot = ar.last_attempt_slot;
+        gr.feedback = GuessFeedback::default();
+        gr.bump = ctx.bumps.guess_record;
+
+        ctx.accounts.global_stats.record_attempt();
+
         if fee == 0 {
             vault.attempt_count = vault.attempt_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
 
             emit!(GuessMade {
                 vault: vault.key(),
                 player: ctx.accounts.player.key(),
+                attempt_index,
                 fee: 0,
                 winner_cut: 0,
                 mega_cut: 0,
+                treasury_cut: 0,
+                creator_cut: 0,
+                referrer: None,
+                referral_cut: 0,
             });
             return Ok(());
         }
 
-        let (winner_cut, mega_cut) = split_fee(fee)?;
-
-        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let mut parts = vault.fee_split.split(fee);
+
+        // Referral cut comes out of the mega share; credited net of any transfer fee.
+        let gross_referral = referral_cut_for(&ctx.accounts.player_profile, &ctx.accounts.global_config, parts.mega);
+        parts.mega -= gross_referral;
+        let mut referral_cut = 0;
+        if gross_referral > 0 {
+            referral_cut = transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.player_fee_ata.to_account_info(),
+                ctx.accounts.referrer_fee_ata.as_mut().ok_or(VaultError::MissingReferrer)?,
+                ctx.accounts.player.to_account_info(),
+                &[],
+                gross_referral,
+            )?;
+            let rp = ctx.accounts.referrer_profile.as_ref().ok_or(VaultError::MissingReferrer)?;
+            let re = ctx.accounts.referral_earnings.as_mut().ok_or(VaultError::MissingReferrer)?;
+            re.referrer = rp.authority;
+            re.mint = ctx.accounts.fee_mint.key();
+            re.earned = re.earned.checked_add(referral_cut).ok_or(VaultError::MathOverflow)?;
+            re.bump = ctx.bumps.referral_earnings.ok_or(VaultError::MissingReferrer)?;
+        }
 
-        // 80% -> mega vault
-        let cpi1 = CpiContext::new(
-            cpi_program.clone(),
-            Transfer {
-                from: ctx.accounts.player_fee_ata.to_account_info(),
-                to: ctx.accounts.mega_vault_fee_ata.to_account_info(),
-                authority: ctx.accounts.player.to_account_info(),
-            },
-        );
-        token::transfer(cpi1, mega_cut)?;
+        // Mega vault share. Cuts are re-measured net of any Token-2022 transfer fee.
+        let mega_cut = transfer_fee_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.fee_mint,
+            ctx.accounts.player_fee_ata.to_account_info(),
+            &mut ctx.accounts.mega_vault_fee_ata,
+            ctx.accounts.player.to_account_info(),
+            &[],
+            parts.mega,
+        )?;
+        ctx.accounts.mega_season.book_fee_inflow(mega_cut)?;
+
+        // Winner share -> vault pool
+        let winner_cut = transfer_fee_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.fee_mint,
+            ctx.accounts.player_fee_ata.to_account_info(),
+            &mut ctx.accounts.vault_fee_ata,
+            ctx.accounts.player.to_account_info(),
+            &[],
+            parts.winner,
+        )?;
 
-        // 20% -> vault pool
-        let cpi2 = CpiContext::new(
-            cpi_program,
-            Transfer {
-                from: ctx.accounts.player_fee_ata.to_account_info(),
-                to: ctx.accounts.vault_fee_ata.to_account_info(),
-                authority: ctx.accounts.player.to_account_info(),
-            },
-        );
-        token::transfer(cpi2, winner_cut)?;
+        let mut treasury_cut = 0;
+        if parts.treasury > 0 {
+            treasury_cut = transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.player_fee_ata.to_account_info(),
+                ctx.accounts.treasury_fee_ata.as_mut().ok_or(VaultError::MissingFeeRecipient)?,
+                ctx.accounts.player.to_account_info(),
+                &[],
+                parts.treasury,
+            )?;
+        }
 
-        vault.total_fees_collected = vault.total_fees_collected.checked_add(fee).ok_or(VaultError::MathOverflow)?;
-        vault.winner_fee_pool = vault
-            .winner_fee_pool
-            .checked_add(winner_cut)
-            .ok_or(VaultError::MathOverflow)?;
+        let mut creator_cut = 0;
+        if parts.creator > 0 {
+            creator_cut = transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.player_fee_ata.to_account_info(),
+                ctx.accounts.creator_fee_ata.as_mut().ok_or(VaultError::MissingFeeRecipient)?,
+                ctx.accounts.player.to_account_info(),
+                &[],
+                parts.creator,
+            )?;
+        }
 
-        vault.attempt_count = vault.attempt_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
-        vault.current_fee_amount = next_fee(vault.current_fee_amount)?;
+        vault.record_paid_attempt(fee, winner_cut)?;
+        ctx.accounts.mint_stats.record_fee(fee, mega_cut);
 
         emit!(GuessMade {
             vault: vault.key(),
             player: ctx.accounts.player.key(),
+            attempt_index,
             fee,
             winner_cut,
             mega_cut,
+            treasury_cut,
+            creator_cut,
+            referrer: ctx.accounts.player_profile.referrer.filter(|_| referral_cut > 0),
+            referral_cut,
         });
 
         Ok(())
     }
 
-    /// Claim win by revealing a secret whose hash matches the vault's committed secret hash.
-    /// First valid claimer becomes the winner.
-    pub fn claim_win(ctx: Context<ClaimWin>, secret: Vec<u8>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Claim phase 1: commit to `sha256(secret || player || nonce)` without revealing the secret.
+    ///
+    /// The slot of the commitment orders competing claims; the earliest valid commitment wins.
+    /// Committing again replaces the previous commitment (and its place in line).
+    pub fn commit_claim(ctx: Context<CommitClaim>, commitment: [u8; 32]) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CLAIM)?;
+        let vault = &ctx.accounts.vault;
+        require!(vault.settlement_mode == SettlementMode::Claim as u8, VaultError::WrongSettlementMode);
         require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
         require!(Clock::get()?.unix_timestamp <= vault.end_ts, VaultError::VaultExpired);
-        require!(vault.winner.is_none(), VaultError::AlreadyHasWinner);
+        require!(!vault.is_self_dealing(&ctx.accounts.player.key()), VaultError::SelfDealingBlocked);
+
+        let slot = Clock::get()?.slot;
+        let cc = &mut ctx.accounts.claim_commitment;
+        cc.vault = vault.key();
+        cc.player = ctx.accounts.player.key();
+        cc.commitment = commitment;
+        cc.slot = slot;
+        cc.bump = ctx.bumps.claim_commitment;
+
+        emit!(ClaimCommitted {
+            vault: vault.key(),
+            player: ctx.accounts.player.key(),
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Claim phase 2: reveal the secret + nonce behind an earlier `commit_claim`.
+    ///
+    /// The reveal must land at least `CLAIM_REVEAL_DELAY_SLOTS` after its commitment and the
+    /// secret must hash to the vault's committed secret hash. Until `end_ts`, a provisional
+    /// winner is displaced by any valid reveal whose commitment is older than theirs.
+    ///
+    /// `secret` is the PIN. On legacy unsalted vaults it is revealed before `end_ts`; salted vaults
+    /// take reveals once `reveal_salt` has published the salt, for `SALTED_CLAIM_WINDOW_SECS`
+    /// (their commitments still had to land before `end_ts`).
+    /// Gated vaults re-check access here (`allowlist_proof` / `gate_token_account`).
+    /// Only players with at least one attempt on the vault (`AttemptRecord`) may claim.
+    pub fn claim_win(
+        ctx: Context<ClaimWin>,
+        secret: Vec<u8>,
+        nonce: [u8; 32],
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CLAIM)?;
+        let clock = Clock::get()?;
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.settlement_mode == SettlementMode::Claim as u8, VaultError::WrongSettlementMode);
+        let provisional = vault.status == VaultStatus::Settled as u8 && vault.winner.is_some();
+        require!(vault.status == VaultStatus::Active as u8 || provisional, VaultError::VaultNotActive);
+        require!(!vault.paid_out, VaultError::AlreadyPaidOut);
+        let salted = vault.secret_scheme == SecretScheme::SaltedSha256 as u8;
+        if salted {
+            require!(vault.salt_revealed, VaultError::SaltNotRevealed);
+            let closes_at = vault
+                .salt_revealed_at
+                .checked_add(SALTED_CLAIM_WINDOW_SECS)
+                .ok_or(VaultError::MathOverflow)?;
+            require!(clock.unix_timestamp <= closes_at, VaultError::VaultExpired);
+        } else {
+            require!(clock.unix_timestamp <= vault.end_ts, VaultError::VaultExpired);
+        }
+
+        let player = ctx.accounts.player.key();
+        require!(!vault.is_self_dealing(&player), VaultError::SelfDealingBlocked);
+        vault.check_access(&player, ctx.accounts.gate_token_account.as_deref(), &allowlist_proof)?;
 
-        let computed = solana_sha256_hasher::hash(&secret).to_bytes();
+        let cc = &ctx.accounts.claim_commitment;
+        let reveal_slot = cc
+            .slot
+            .checked_add(CLAIM_REVEAL_DELAY_SLOTS)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(clock.slot >= reveal_slot, VaultError::ClaimRevealTooEarly);
+
+        let expected = solana_sha256_hasher::hashv(&[&secret, player.as_ref(), &nonce]).to_bytes();
+        require!(expected == cc.commitment, VaultError::ClaimCommitmentMismatch);
+
+        let computed = if salted {
+            solana_sha256_hasher::hashv(&[&vault.salt, &secret]).to_bytes()
+        } else {
+            solana_sha256_hasher::hash(&secret).to_bytes()
+        };
         require!(computed == vault.secret_hash, VaultError::BadSecret);
 
-        vault.winner = Some(ctx.accounts.player.key());
+        // Only players who made an attempt may claim, optionally within a slot window of it.
+        let ar = &ctx.accounts.attempt_record;
+        require!(ar.attempts > 0, VaultError::NoAttempt);
+        if vault.claim_window_slots > 0 {
+            let deadline = ar
+                .last_attempt_slot
+                .checked_add(vault.claim_window_slots)
+                .ok_or(VaultError::MathOverflow)?;
+            require!(cc.slot <= deadline, VaultError::ClaimWindowElapsed);
+        }
+
+        // An older commitment outranks the provisional winner; roll back their win.
+        if let Some(prev) = vault.winner {
+            require!(cc.slot < vault.claim_slot, VaultError::AlreadyHasWinner);
+
+            let displaced = ctx
+                .accounts
+                .displaced_profile
+                .as_mut()
+                .ok_or(VaultError::BadDisplacedProfile)?;
+            require_keys_eq!(displaced.authority, prev, VaultError::BadDisplacedProfile);
+            displaced.wins = displaced.wins.saturating_sub(1);
+            displaced.score = displaced.score.saturating_sub(SCORE_PER_WIN);
+        }
+
+        if !provisional {
+            ctx.accounts.global_stats.record_vault_settled();
+        }
+        vault.winner = Some(player);
+        vault.claim_slot = cc.slot;
         vault.status = VaultStatus::Settled as u8;
-        vault.settled_at = Some(Clock::get()?.unix_timestamp);
+        ctx.accounts.vault_index.load_mut()?.set_status(vault.vault_id, vault.status);
+        vault.settled_at = Some(clock.unix_timestamp);
 
         // Touch player profile (init if needed) + record win.
         let pp = &mut ctx.accounts.player_profile;
@@ -352,70 +990,272 @@ pub mod vault_game {
         Ok(())
     }
 
-    /// Claim prize + vault pool as the winner after the vault expires.
-    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
-        let now = Clock::get()?.unix_timestamp;
+    /// Referee path: settle a referee-mode vault from a signed verdict, never posting the secret.
+    ///
+    /// The previous instruction in the transaction must be an Ed25519 program instruction in which
+    /// `GlobalConfig.referee` signs `verdict_message(vault, player, attempt_index, verdict)`.
+    /// A `Correct` verdict makes the player the winner; `Incorrect` is only recorded as an event.
+    ///
+    /// The verdict is written to the attempt's `GuessRecord` as its feedback.
+    pub fn submit_verdict(ctx: Context<SubmitVerdict>, attempt_index: u64, verdict: u8) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CLAIM)?;
+        let clock = Clock::get()?;
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.settlement_mode == SettlementMode::Referee as u8, VaultError::WrongSettlementMode);
+        require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
+        require!(clock.unix_timestamp <= vault.end_ts, VaultError::VaultExpired);
+        require!(
+            verdict == Verdict::Incorrect as u8 || verdict == Verdict::Correct as u8,
+            VaultError::BadVerdict
+        );
 
-        // Pull values out first to avoid borrow conflicts during CPI.
-        let end_ts = ctx.accounts.vault.end_ts;
-        let paid_out = ctx.accounts.vault.paid_out;
-        let winner = ctx.accounts.vault.winner;
-        let prize_amount = ctx.accounts.vault.prize_amount;
-        let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
-        let bump = ctx.accounts.vault.bump;
+        let player = ctx.accounts.player.key();
+        require!(!vault.is_self_dealing(&player), VaultError::SelfDealingBlocked);
+        require_keys_eq!(ctx.accounts.guess_record.player, player, VaultError::GuessRecordMismatch);
+        require!(ctx.accounts.guess_record.feedback.verdict.is_none(), VaultError::FeedbackAlreadySet);
 
-        require!(now > end_ts, VaultError::VaultNotExpired);
-        require!(!paid_out, VaultError::AlreadyPaidOut);
-        require!(winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
+        // The referee's signature is checked by the Ed25519 program in the preceding instruction.
+        let ixs = ctx.accounts.instructions.to_account_info();
+        let current = load_current_index_checked(&ixs)?;
+        let prev = current.checked_sub(1).ok_or(VaultError::BadVerdictSignature)?;
+        let sig_ix = load_instruction_at_checked(prev as usize, &ixs)?;
+        let msg = verdict_message(&vault.key(), &player, attempt_index, verdict);
+        verify_ed25519_ix(&sig_ix, &ctx.accounts.global_config.referee, &msg)?;
 
-        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault_id_bytes.as_ref(), &[bump]]];
+        ctx.accounts.guess_record.feedback.verdict = Some(verdict);
+
+        emit!(VerdictSubmitted {
+            vault: vault.key(),
+            player,
+            attempt_index,
+            verdict,
+        });
+
+        if verdict != Verdict::Correct as u8 {
+            return Ok(());
+        }
+
+        ctx.accounts.global_stats.record_vault_settled();
+        vault.winner = Some(player);
+        vault.status = VaultStatus::Settled as u8;
+        ctx.accounts.vault_index.load_mut()?.set_status(vault.vault_id, vault.status);
+        vault.settled_at = Some(clock.unix_timestamp);
+
+        let pp = &mut ctx.accounts.player_profile;
+        pp.authority = player;
+        pp.wins = pp.wins.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        pp.score = pp.score.checked_add(SCORE_PER_WIN).ok_or(VaultError::MathOverflow)?;
+        pp.last_seen_ts = clock.unix_timestamp;
+        pp.bump = ctx.bumps.player_profile;
+
+        emit!(VaultWon {
+            vault: vault.key(),
+            winner: player,
+        });
+
+        Ok(())
+    }
+
+    /// Creator or referee proves Mastermind feedback on a guess against the vault's hint root.
+    ///
+    /// Opens the guess (`sha256(digits || player || nonce)`) and checks every revealed leaf against
+    /// `Vault.hint_root`. Counts are computed on-chain from revealed positions only: `exact` where the
+    /// leaf digit equals the guessed digit, `partial` for digits shared between revealed non-exact
+    /// positions. Unrevealed positions add nothing, so feedback can be withheld but never inflated.
+    ///
+    /// Every revealed leaf publishes that position's secret digit to everyone, not just the guesser.
+    /// To bound this, a leaf may only be revealed if its digit appears in the guess, and fewer than
+    /// `pin_len` distinct positions may ever be opened on a vault (`Vault.hint_positions_opened`),
+    /// so hints never publish the whole PIN.
+    pub fn submit_hint(
+        ctx: Context<SubmitHint>,
+        attempt_index: u64,
+        guess_digits: Vec<u8>,
+        guess_nonce: [u8; 32],
+        proofs: Vec<DigitProof>,
+    ) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_GUESS)?;
+        let vault = &mut ctx.accounts.vault;
+        let root = vault.hint_root.ok_or(VaultError::HintsNotEnabled)?;
+
+        let authority = ctx.accounts.authority.key();
+        let is_referee = ctx.accounts.global_config.referee == authority;
+        require!(authority == vault.creator || is_referee, VaultError::NotAuthorized);
+
+        let gr = &mut ctx.accounts.guess_record;
+        require!(gr.feedback.hint.is_none(), VaultError::FeedbackAlreadySet);
+        require!(guess_digits.len() == vault.pin_len as usize, VaultError::BadGuessOpening);
+        require!(guess_digits.iter().all(|d| *d <= 9), VaultError::BadGuessOpening);
+        let opened = solana_sha256_hasher::hashv(&[&guess_digits, gr.player.as_ref(), &guess_nonce]).to_bytes();
+        require!(opened == gr.guess, VaultError::BadGuessOpening);
+
+        let depth = hints::depth(guess_digits.len());
+        let mut revealed: u8 = 0;
+        let mut exact: u8 = 0;
+        let mut guess_counts = [0u8; 10];
+        let mut secret_counts = [0u8; 10];
+        for p in proofs.iter() {
+            let pos = p.position as usize;
+            require!(pos < guess_digits.len() && p.digit <= 9, VaultError::BadHintProof);
+            require!(revealed & (1 << pos) == 0, VaultError::BadHintProof);
+            require!(guess_digits.contains(&p.digit), VaultError::HintDigitNotGuessed);
+            revealed |= 1 << pos;
+            require!(
+                p.siblings.len() == depth && hints::verify_proof(&root, p.position, p.digit, &p.salt, &p.siblings),
+                VaultError::BadHintProof
+            );
+
+            if p.digit == guess_digits[pos] {
+                exact += 1;
+            } else {
+                guess_counts[guess_digits[pos] as usize] += 1;
+                secret_counts[p.digit as usize] += 1;
+            }
+        }
+        let partial: u8 = guess_counts
+            .iter()
+            .zip(secret_counts.iter())
+            .map(|(g, s)| *g.min(s))
+            .sum();
+
+        let opened = vault.hint_positions_opened | revealed;
+        require!(opened.count_ones() < vault.pin_len as u32, VaultError::HintLimitReached);
+        vault.hint_positions_opened = opened;
+
+        gr.feedback.hint = Some(HintCounts { exact, partial });
+
+        emit!(HintSubmitted {
+            vault: vault.key(),
+            attempt_index,
+            exact,
+            partial,
+        });
+
+        Ok(())
+    }
+
+    /// Creator reveals the salt (and PIN) of a salted vault after expiry.
+    ///
+    /// Makes the outcome auditable: anyone can check `sha256(salt || pin) == secret_hash`. On claim-mode
+    /// vaults it also opens the `claim_win` reveal window.
+    /// Required before `reclaim_prize` on salted vaults, so an unwinnable commitment can't be reclaimed.
+    ///
+    /// On hint-enabled vaults `hint_salts` (one per position) must rebuild `hint_root` from the PIN's
+    /// digits, so a creator whose hints were built over a different PIN cannot reclaim.
+    pub fn reveal_salt(
+        ctx: Context<RevealSalt>,
+        salt: [u8; 32],
+        pin: Vec<u8>,
+        hint_salts: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let vault = &mut ctx.accounts.vault;
+        require!(Clock::get()?.unix_timestamp > vault.end_ts, VaultError::VaultNotExpired);
+        require!(vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        require!(vault.secret_scheme == SecretScheme::SaltedSha256 as u8, VaultError::BadSecretScheme);
+        require!(!vault.salt_revealed, VaultError::SaltAlreadyRevealed);
+
+        let computed = solana_sha256_hasher::hashv(&[&salt, &pin]).to_bytes();
+        require!(computed == vault.secret_hash, VaultError::BadSecret);
+
+        if let Some(hint_root) = vault.hint_root {
+            // The PIN is committed as ASCII text; hint leaves hold digit values.
+            let digits: Vec<u8> = pin.iter().map(|c| c.wrapping_sub(b'0')).collect();
+            require!(
+                digits.len() == vault.pin_len as usize
+                    && digits.iter().all(|d| *d <= 9)
+                    && hint_salts.len() == digits.len(),
+                VaultError::BadHintRoot
+            );
+            let levels = hints::build_tree(&digits, &hint_salts);
+            require!(hints::root(&levels) == hint_root, VaultError::BadHintRoot);
+        }
+
+        vault.salt = salt;
+        vault.salt_revealed = true;
+        vault.salt_revealed_at = Clock::get()?.unix_timestamp;
+
+        emit!(SaltRevealed {
+            vault: vault.key(),
+            salt,
+            pin,
+        });
+
+        Ok(())
+    }
+
+    /// Claim prize + vault pool as the winner after the vault expires.
+    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Pull values out first to avoid borrow conflicts during CPI.
+        let paid_out = ctx.accounts.vault.paid_out;
+        let winner = ctx.accounts.vault.winner;
+        let prize_amount = ctx.accounts.vault.prize_amount;
+        let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
+        let bump = ctx.accounts.vault.bump;
+
+        require!(!ctx.accounts.vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        ctx.accounts.vault.check_claims_closed(now)?;
+        require!(!paid_out, VaultError::AlreadyPaidOut);
+        require!(winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault_id_bytes.as_ref(), &[bump]]];
+
+        // Winner pool (winner shares of attempt fees) sits in the same account as the prize; read it
+        // before the prize transfer leaves `vault_fee_ata.amount` stale.
+        let pool_amount =
+            fees::vault_pool(ctx.accounts.vault_fee_ata.amount, prize_amount).ok_or(VaultError::MathOverflow)?;
 
         // Transfer locked prize + vault fee pool to the winner.
         let total_prize = prize_amount;
+        let mut paid = 0u64;
 
         if total_prize > 0 {
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi = CpiContext::new_with_signer(
-                cpi_program.clone(),
-                Transfer {
-                    from: ctx.accounts.vault_prize_ata.to_account_info(),
-                    to: ctx.accounts.winner_fee_ata.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
+            paid += transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.vault_prize_ata.to_account_info(),
+                &mut ctx.accounts.winner_fee_ata,
+                ctx.accounts.vault.to_account_info(),
                 signer_seeds,
-            );
-            token::transfer(cpi, total_prize)?;
+                total_prize,
+            )?;
         }
 
-        // Winner pool (80% slices of attempt fees) lives in vault_fee_ata.
-        let pool_amount = ctx.accounts.vault_fee_ata.amount;
         if pool_amount > 0 {
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi = CpiContext::new_with_signer(
-                cpi_program,
-                Transfer {
-                    from: ctx.accounts.vault_fee_ata.to_account_info(),
-                    to: ctx.accounts.winner_fee_ata.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
+            paid += transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.vault_fee_ata.to_account_info(),
+                &mut ctx.accounts.winner_fee_ata,
+                ctx.accounts.vault.to_account_info(),
                 signer_seeds,
-            );
-            token::transfer(cpi, pool_amount)?;
+                pool_amount,
+            )?;
         }
 
         ctx.accounts.vault.paid_out = true;
+        ctx.accounts.mint_stats.record_prize(paid);
+
+        emit!(PrizeClaimed {
+            vault: ctx.accounts.vault.key(),
+            winner: ctx.accounts.winner.key(),
+            amount: paid,
+        });
 
         Ok(())
     }
 
     /// Reclaim prize as the creator after expiry if nobody wins.
     /// Creator receives: locked prize + 50% of vault pool.
-    /// Mega vault receives: remaining 50% of vault pool (in addition to its 20% live cut).
+    /// Mega vault receives: remaining 50% of vault pool (in addition to its live share of each fee).
     pub fn reclaim_prize(ctx: Context<ReclaimPrize>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
         let now = Clock::get()?.unix_timestamp;
 
         // Pull values out first to avoid borrow conflicts during CPI.
-        let end_ts = ctx.accounts.vault.end_ts;
         let paid_out = ctx.accounts.vault.paid_out;
         let winner = ctx.accounts.vault.winner;
         let creator_key = ctx.accounts.vault.creator;
@@ -423,66 +1263,363 @@ pub mod vault_game {
         let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
         let bump = ctx.accounts.vault.bump;
 
-        require!(now > end_ts, VaultError::VaultNotExpired);
+        require!(!ctx.accounts.vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        ctx.accounts.vault.check_claims_closed(now)?;
         require!(!paid_out, VaultError::AlreadyPaidOut);
         require!(winner.is_none(), VaultError::AlreadyHasWinner);
         require!(creator_key == ctx.accounts.creator.key(), VaultError::NotCreator);
+        if ctx.accounts.vault.secret_scheme == SecretScheme::SaltedSha256 as u8 {
+            require!(ctx.accounts.vault.salt_revealed, VaultError::SaltNotRevealed);
+        }
 
         let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault_id_bytes.as_ref(), &[bump]]];
 
+        // Pool and prize share the vault's token account; read the pool before any transfer.
+        let pool_amount =
+            fees::vault_pool(ctx.accounts.vault_fee_ata.amount, prize_amount).ok_or(VaultError::MathOverflow)?;
+
         // Return locked prize.
         let total_prize = prize_amount;
+        let mut returned = 0u64;
+        let mut mega_received = 0u64;
         if total_prize > 0 {
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi = CpiContext::new_with_signer(
-                cpi_program.clone(),
-                Transfer {
-                    from: ctx.accounts.vault_prize_ata.to_account_info(),
-                    to: ctx.accounts.creator_fee_ata.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
+            returned += transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.vault_prize_ata.to_account_info(),
+                &mut ctx.accounts.creator_fee_ata,
+                ctx.accounts.vault.to_account_info(),
                 signer_seeds,
-            );
-            token::transfer(cpi, total_prize)?;
+                total_prize,
+            )?;
         }
 
         // Split vault pool 50/50 between creator and mega vault.
-        let pool_amount = ctx.accounts.vault_fee_ata.amount;
         if pool_amount > 0 {
             let creator_cut = pool_amount / 2;
             let mega_cut = pool_amount.checked_sub(creator_cut).ok_or(VaultError::MathOverflow)?;
 
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-
             if creator_cut > 0 {
-                let cpi = CpiContext::new_with_signer(
-                    cpi_program.clone(),
-                    Transfer {
-                        from: ctx.accounts.vault_fee_ata.to_account_info(),
-                        to: ctx.accounts.creator_fee_ata.to_account_info(),
-                        authority: ctx.accounts.vault.to_account_info(),
-                    },
+                returned += transfer_fee_tokens(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.fee_mint,
+                    ctx.accounts.vault_fee_ata.to_account_info(),
+                    &mut ctx.accounts.creator_fee_ata,
+                    ctx.accounts.vault.to_account_info(),
                     signer_seeds,
-                );
-                token::transfer(cpi, creator_cut)?;
+                    creator_cut,
+                )?;
             }
 
             if mega_cut > 0 {
-                let cpi = CpiContext::new_with_signer(
-                    cpi_program,
-                    Transfer {
-                        from: ctx.accounts.vault_fee_ata.to_account_info(),
-                        to: ctx.accounts.mega_vault_fee_ata.to_account_info(),
-                        authority: ctx.accounts.vault.to_account_info(),
-                    },
+                let received = transfer_fee_tokens(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.fee_mint,
+                    ctx.accounts.vault_fee_ata.to_account_info(),
+                    &mut ctx.accounts.mega_vault_fee_ata,
+                    ctx.accounts.vault.to_account_info(),
                     signer_seeds,
-                );
-                token::transfer(cpi, mega_cut)?;
+                    mega_cut,
+                )?;
+                ctx.accounts.mega_season.book_reclaim_inflow(received)?;
+                mega_received = received;
             }
         }
 
         ctx.accounts.vault.paid_out = true;
         ctx.accounts.vault.status = VaultStatus::Cancelled as u8;
+        ctx.accounts
+            .vault_index
+            .load_mut()?
+            .set_status(ctx.accounts.vault.vault_id, VaultStatus::Cancelled as u8);
+        ctx.accounts.global_stats.record_vault_settled();
+        ctx.accounts.mint_stats.record_reclaim(returned, mega_received);
+
+        emit!(PrizeReclaimed {
+            vault: ctx.accounts.vault.key(),
+            creator: creator_key,
+            amount: returned,
+            mega_cut: mega_received,
+        });
+
+        Ok(())
+    }
+
+    /// SOL vault: claim prize + vault pool as the winner after the vault expires.
+    ///
+    /// Pays out every lamport the Vault PDA holds above its rent-exempt minimum.
+    pub fn claim_prize_sol(ctx: Context<ClaimPrizeSol>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.vault;
+
+        require!(vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        vault.check_claims_closed(now)?;
+        require!(!vault.paid_out, VaultError::AlreadyPaidOut);
+        require!(vault.winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
+
+        let payout = lamports_above_rent(&vault.to_account_info())?;
+        if payout > 0 {
+            ctx.accounts.vault.sub_lamports(payout)?;
+            ctx.accounts.winner.add_lamports(payout)?;
+        }
+
+        ctx.accounts.vault.paid_out = true;
+        ctx.accounts.mint_stats.record_prize(payout);
+
+        emit!(PrizeClaimed {
+            vault: ctx.accounts.vault.key(),
+            winner: ctx.accounts.winner.key(),
+            amount: payout,
+        });
+
+        Ok(())
+    }
+
+    /// SOL vault: reclaim prize as the creator after expiry if nobody wins.
+    /// Creator receives: locked prize + 50% of vault pool. Mega vault PDA receives the other 50%.
+    pub fn reclaim_prize_sol(ctx: Context<ReclaimPrizeSol>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.vault;
+
+        require!(vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        vault.check_claims_closed(now)?;
+        require!(!vault.paid_out, VaultError::AlreadyPaidOut);
+        require!(vault.winner.is_none(), VaultError::AlreadyHasWinner);
+        require!(vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        if vault.secret_scheme == SecretScheme::SaltedSha256 as u8 {
+            require!(vault.salt_revealed, VaultError::SaltNotRevealed);
+        }
+
+        // Everything above rent is prize + pool; the prize goes back first.
+        let available = lamports_above_rent(&vault.to_account_info())?;
+        let prize = vault.prize_amount.min(available);
+        let pool_amount = available.checked_sub(prize).ok_or(VaultError::MathOverflow)?;
+        let pool_creator_cut = pool_amount / 2;
+        let mega_cut = pool_amount.checked_sub(pool_creator_cut).ok_or(VaultError::MathOverflow)?;
+        let creator_cut = prize.checked_add(pool_creator_cut).ok_or(VaultError::MathOverflow)?;
+        ctx.accounts.mega_season.book_reclaim_inflow(mega_cut)?;
+
+        if creator_cut > 0 {
+            ctx.accounts.vault.sub_lamports(creator_cut)?;
+            ctx.accounts.creator.add_lamports(creator_cut)?;
+        }
+        if mega_cut > 0 {
+            ctx.accounts.vault.sub_lamports(mega_cut)?;
+            ctx.accounts.mega_vault.add_lamports(mega_cut)?;
+        }
+
+        ctx.accounts.vault.paid_out = true;
+        ctx.accounts.vault.status = VaultStatus::Cancelled as u8;
+        ctx.accounts
+            .vault_index
+            .load_mut()?
+            .set_status(ctx.accounts.vault.vault_id, VaultStatus::Cancelled as u8);
+        ctx.accounts.global_stats.record_vault_settled();
+        ctx.accounts.mint_stats.record_reclaim(creator_cut, mega_cut);
+
+        emit!(PrizeReclaimed {
+            vault: ctx.accounts.vault.key(),
+            creator: ctx.accounts.creator.key(),
+            amount: creator_cut,
+            mega_cut,
+        });
+
+        Ok(())
+    }
+
+    /// Mega season winner claims the mega pool (SPL) once the season's challenge vault has expired.
+    ///
+    /// Pays `GlobalConfig.mega_payout_bps` of the season jackpot (the whole jackpot if unset),
+    /// closes the season as won and opens the next one with the remainder. The winner pays the
+    /// next season's rent.
+    pub fn claim_mega_vault(ctx: Context<ClaimMegaVault>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_PAYOUT)?;
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.vault;
+
+        require!(!vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        vault.check_claims_closed(now)?;
+        require!(!vault.mega_paid_out, VaultError::AlreadyPaidOut);
+        require!(vault.winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
+        require!(
+            ctx.accounts.mega_season.challenge_vault == Some(vault.key()),
+            VaultError::NotMegaChallengeVault
+        );
+
+        let jackpot = ctx.accounts.mega_season.jackpot()?;
+        let pool = jackpot.min(ctx.accounts.mega_vault_fee_ata.amount);
+        let amount = mega_payout(pool, ctx.accounts.global_config.mega_payout_bps);
+
+        if amount > 0 {
+            let bump = ctx.accounts.mega_vault.bump;
+            let signer_seeds: &[&[&[u8]]] = &[&[b"mega_vault", &[bump]]];
+            transfer_fee_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.fee_mint,
+                ctx.accounts.mega_vault_fee_ata.to_account_info(),
+                &mut ctx.accounts.winner_fee_ata,
+                ctx.accounts.mega_vault.to_account_info(),
+                signer_seeds,
+                amount,
+            )?;
+        }
+
+        ctx.accounts.vault.mega_paid_out = true;
+        ctx.accounts.mint_stats.record_prize(amount);
+        let season_id = close_won_season(
+            &mut ctx.accounts.mega_season,
+            &mut ctx.accounts.next_season,
+            ctx.accounts.winner.key(),
+            amount,
+            now,
+            ctx.bumps.next_season,
+        )?;
+
+        emit!(MegaVaultWon {
+            vault: ctx.accounts.vault.key(),
+            winner: ctx.accounts.winner.key(),
+            fee_mint: ctx.accounts.vault.fee_mint,
+            season_id,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Mega season winner claims the SOL mega pool (lamports above the MegaVault PDA's rent).
+    pub fn claim_mega_vault_sol(ctx: Context<ClaimMegaVaultSol>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_PAYOUT)?;
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.vault;
+
+        require!(vault.is_sol_fee, VaultError::WrongFeeCurrency);
+        vault.check_claims_closed(now)?;
+        require!(!vault.mega_paid_out, VaultError::AlreadyPaidOut);
+        require!(vault.winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
+        require!(
+            ctx.accounts.mega_season.challenge_vault == Some(vault.key()),
+            VaultError::NotMegaChallengeVault
+        );
+
+        let jackpot = ctx.accounts.mega_season.jackpot()?;
+        let pool = jackpot.min(lamports_above_rent(&ctx.accounts.mega_vault.to_account_info())?);
+        let amount = mega_payout(pool, ctx.accounts.global_config.mega_payout_bps);
+
+        if amount > 0 {
+            ctx.accounts.mega_vault.sub_lamports(amount)?;
+            ctx.accounts.winner.add_lamports(amount)?;
+        }
+
+        ctx.accounts.vault.mega_paid_out = true;
+        ctx.accounts.mint_stats.record_prize(amount);
+        let season_id = close_won_season(
+            &mut ctx.accounts.mega_season,
+            &mut ctx.accounts.next_season,
+            ctx.accounts.winner.key(),
+            amount,
+            now,
+            ctx.bumps.next_season,
+        )?;
+
+        emit!(MegaVaultWon {
+            vault: ctx.accounts.vault.key(),
+            winner: ctx.accounts.winner.key(),
+            fee_mint: Pubkey::default(),
+            season_id,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: grow a vault created before the fields appended after `bump` to `Vault::LEN`.
+    ///
+    /// New fields take the legacy behaviour (unsalted claim settlement, 1.2x uncapped ladder, 80/20
+    /// split, open access). The payer covers the extra rent, so a SOL vault's balance above rent is
+    /// unchanged. Legacy vaults never counted their rewards, so they cannot be cancelled or closed.
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let info = ctx.accounts.vault.to_account_info();
+        require!(info.data_len() == 8 + LegacyVault::LEN, VaultError::NotLegacyVault);
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(&data[..8] == Vault::DISCRIMINATOR, VaultError::NotLegacyVault);
+            LegacyVault::deserialize(&mut &data[8..])?
+        };
+
+        let new_len = 8 + Vault::LEN;
+        let rent = Rent::get()?;
+        let top_up = rent
+            .minimum_balance(new_len)
+            .saturating_sub(rent.minimum_balance(info.data_len()));
+        pay_lamports(
+            &ctx.accounts.payer.to_account_info(),
+            &info,
+            &ctx.accounts.system_program.to_account_info(),
+            top_up,
+        )?;
+        info.resize(new_len)?;
+
+        let vault = Vault::from_legacy(legacy);
+        let mut data = info.try_borrow_mut_data()?;
+        vault.try_serialize(&mut &mut data[..])?;
+
+        Ok(())
+    }
+
+    /// Permissionless: grow a profile created before `referrer` was appended to `PlayerProfile::LEN`.
+    ///
+    /// Until then the profile fails to deserialize, so the player cannot guess, claim or bind a
+    /// referrer. The appended fields start zeroed (no referrer, no earnings); the payer covers any
+    /// rent shortfall.
+    pub fn migrate_player_profile(ctx: Context<MigratePlayerProfile>) -> Result<()> {
+        let info = ctx.accounts.player_profile.to_account_info();
+        require!(info.data_len() == 8 + PlayerProfile::LEGACY_LEN, VaultError::NotLegacyProfile);
+        require!(
+            &info.try_borrow_data()?[..8] == PlayerProfile::DISCRIMINATOR,
+            VaultError::NotLegacyProfile
+        );
+
+        let new_len = 8 + PlayerProfile::LEN;
+        let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+        pay_lamports(
+            &ctx.accounts.payer.to_account_info(),
+            &info,
+            &ctx.accounts.system_program.to_account_info(),
+            top_up,
+        )?;
+        info.resize(new_len)?;
+        info.try_borrow_mut_data()?[8 + PlayerProfile::LEGACY_LEN..].fill(0);
+
+        Ok(())
+    }
+
+    /// Permissionless: write a vault's current state into its `VaultIndexPage`.
+    ///
+    /// Only needed for vaults created before the index existed (after `migrate_vault` for pre-upgrade
+    /// vaults); `create_vault*` index new vaults.
+    pub fn index_vault(ctx: Context<IndexVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let mut page = load_or_init(&ctx.accounts.vault_index)?;
+        page.init(VaultIndexPage::page_of(vault.vault_id), ctx.bumps.vault_index);
+        page.insert(vault.vault_id, vault.end_ts, vault.is_sol_fee);
+        page.set_status(vault.vault_id, vault.status);
+        Ok(())
+    }
+
+    /// Creator-only: replace the vault's title, description, category and media URI, or add them to
+    /// a vault created without `CreateVaultArgs.metadata`.
+    ///
+    /// Metadata stays editable until the first attempt. The vault records that it exists
+    /// (`Vault.has_metadata`) so `close_vault` closes it with the vault.
+    pub fn set_vault_metadata(ctx: Context<SetVaultMetadata>, args: VaultMetadataArgs) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CREATE)?;
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
+        require!(vault.attempt_count == 0, VaultError::MetadataLocked);
+        write_vault_metadata(vault, &mut ctx.accounts.vault_metadata, args, ctx.bumps.vault_metadata)?;
 
         Ok(())
     }
@@ -491,8 +1628,10 @@ pub mod vault_game {
     ///
     /// The reward is escrowed in a vault-owned (PDA) token account.
     pub fn add_reward(ctx: Context<AddReward>, amount: u64) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_CREATE)?;
         require!(amount > 0, VaultError::BadRewardAmount);
         require!(ctx.accounts.vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        require!(ctx.accounts.vault.status != VaultStatus::Cancelled as u8, VaultError::VaultNotActive);
 
         // (Re)initialize reward record.
         let reward = &mut ctx.accounts.reward;
@@ -503,6 +1642,10 @@ pub mod vault_game {
             reward.amount = 0;
             reward.claimed = false;
             reward.bump = ctx.bumps.reward;
+            ctx.accounts.vault.reward_escrows =
+                ctx.accounts.vault.reward_escrows.checked_add(1).ok_or(VaultError::MathOverflow)?;
+            ctx.accounts.vault.reward_accounts =
+                ctx.accounts.vault.reward_accounts.checked_add(1).ok_or(VaultError::MathOverflow)?;
         } else {
             require_keys_eq!(reward.mint, ctx.accounts.reward_mint.key(), VaultError::RewardWrongMint);
             require_keys_eq!(reward.token_program, ctx.accounts.token_program.key(), VaultError::RewardWrongTokenProgram);
@@ -524,14 +1667,239 @@ pub mod vault_game {
         token_interface::transfer_checked(cpi, amount, decimals)?;
 
         reward.amount = reward.amount.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+        ctx.accounts.global_stats.record_reward_added();
+
+        emit!(RewardAdded {
+            vault: ctx.accounts.vault.key(),
+            mint: reward.mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Creator cancels a vault nobody has attempted yet, getting back the prize and every reward escrow.
+    ///
+    /// SPL vaults pass `fee_mint`, `vault_prize_ata`, `creator_fee_ata` and `token_program`; SOL vaults
+    /// get their lamports above rent back. Each open `VaultReward` is passed in `remaining_accounts`
+    /// as `[reward, reward_mint, vault_reward_ata, creator_reward_ata, reward_token_program]`, and all
+    /// of them must be present.
+    pub fn cancel_vault<'info>(ctx: Context<'_, '_, 'info, 'info, CancelVault<'info>>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let vault = &ctx.accounts.vault;
+        require!(vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        require!(vault.status == VaultStatus::Active as u8, VaultError::VaultNotActive);
+        require!(vault.attempt_count == 0, VaultError::VaultHasAttempts);
+        require!(!vault.paid_out, VaultError::AlreadyPaidOut);
+        require!(!vault.rewards_untracked, VaultError::RewardsUntracked);
+
+        let vault_id_bytes = vault.vault_id.to_le_bytes();
+        let bump = vault.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault_id_bytes.as_ref(), &[bump]]];
+
+        // Return the prize.
+        let prize_returned = if vault.is_sol_fee {
+            let amount = lamports_above_rent(&vault.to_account_info())?;
+            if amount > 0 {
+                ctx.accounts.vault.sub_lamports(amount)?;
+                ctx.accounts.creator.add_lamports(amount)?;
+            }
+            amount
+        } else {
+            let (Some(fee_mint), Some(vault_prize_ata), Some(creator_fee_ata), Some(token_program)) = (
+                ctx.accounts.fee_mint.as_ref(),
+                ctx.accounts.vault_prize_ata.as_ref(),
+                ctx.accounts.creator_fee_ata.as_mut(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(VaultError::WrongFeeCurrency);
+            };
+            require_keys_eq!(fee_mint.key(), vault.fee_mint, VaultError::WrongFeeMint);
+            let amount = vault_prize_ata.amount;
+            if amount > 0 {
+                transfer_fee_tokens(
+                    token_program,
+                    fee_mint,
+                    vault_prize_ata.to_account_info(),
+                    creator_fee_ata,
+                    ctx.accounts.vault.to_account_info(),
+                    signer_seeds,
+                    amount,
+                )?;
+            }
+            amount
+        };
 
+        // Return every reward escrow.
+        let creator = ctx.accounts.creator.key();
+        let vault_key = ctx.accounts.vault.key();
+        // Not `is_multiple_of`: that needs Rust 1.87, newer than the Solana platform toolchain.
+        #[allow(clippy::manual_is_multiple_of)]
+        let whole_groups = ctx.remaining_accounts.len() % 5 == 0;
+        require!(whole_groups, VaultError::RewardsOutstanding);
+        let mut rewards_returned: u32 = 0;
+        for group in ctx.remaining_accounts.chunks(5) {
+            let mut reward = Account::<VaultReward>::try_from(&group[0])?;
+            let reward_mint = InterfaceAccount::<IMint>::try_from(&group[1])?;
+            let vault_reward_ata = InterfaceAccount::<ITokenAccount>::try_from(&group[2])?;
+            let creator_reward_ata = InterfaceAccount::<ITokenAccount>::try_from(&group[3])?;
+            let token_program = Interface::<TokenInterface>::try_from(&group[4])?;
+
+            require_keys_eq!(reward.vault, vault_key, VaultError::RewardWrongVault);
+            require_keys_eq!(reward.mint, reward_mint.key(), VaultError::RewardWrongMint);
+            require_keys_eq!(reward.token_program, token_program.key(), VaultError::RewardWrongTokenProgram);
+            require!(!reward.claimed, VaultError::RewardAlreadyClaimed);
+            require_keys_eq!(
+                vault_reward_ata.key(),
+                get_associated_token_address_with_program_id(&vault_key, &reward.mint, &reward.token_program),
+                VaultError::RewardWrongVault
+            );
+            require_keys_eq!(creator_reward_ata.owner, creator, VaultError::WrongFeeOwner);
+            require_keys_eq!(creator_reward_ata.mint, reward.mint, VaultError::RewardWrongMint);
+
+            if reward.amount > 0 {
+                let cpi = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault_reward_ata.to_account_info(),
+                        mint: reward_mint.to_account_info(),
+                        to: creator_reward_ata.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                );
+                token_interface::transfer_checked(cpi, reward.amount, reward_mint.decimals)?;
+            }
+
+            reward.amount = 0;
+            reward.claimed = true;
+            reward.exit(&crate::ID)?;
+            rewards_returned += 1;
+        }
+
+        let vault = &mut ctx.accounts.vault;
+        vault.reward_escrows = vault.reward_escrows.saturating_sub(rewards_returned);
+        require!(vault.reward_escrows == 0, VaultError::RewardsOutstanding);
+
+        vault.status = VaultStatus::Cancelled as u8;
+        vault.paid_out = true;
+        vault.settled_at = Some(Clock::get()?.unix_timestamp);
+        ctx.accounts.global_stats.record_vault_settled();
+        ctx.accounts.vault_index.load_mut()?.set_status(vault.vault_id, vault.status);
+
+        emit!(VaultCancelled {
+            vault: vault.key(),
+            creator,
+            prize_returned,
+            rewards_returned,
+        });
+
+        Ok(())
+    }
+
+    /// Creator closes a paid-out vault and reclaims the rent of its accounts.
+    ///
+    /// SPL vaults pass `fee_mint`, `vault_fee_ata` (the one token account holding prize and pool) and
+    /// `token_program`; it must be empty (Token-2022 accounts also need withheld transfer fees harvested).
+    /// Every `VaultReward` is passed in `remaining_accounts` as
+    /// `[reward, vault_reward_ata, reward_token_program]`; each must be claimed and its ATA empty.
+    /// The open mega season for the vault's currency guards against closing an unpaid mega challenge.
+    /// `vault_metadata` is required once `set_vault_metadata` has created it, and is closed as well.
+    pub fn close_vault<'info>(ctx: Context<'_, '_, 'info, 'info, CloseVault<'info>>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
+        let vault = &ctx.accounts.vault;
+        require!(vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
+        require!(vault.paid_out, VaultError::VaultNotPaidOut);
+        require!(!vault.rewards_untracked, VaultError::RewardsUntracked);
+        require!(vault.reward_escrows == 0, VaultError::RewardsOutstanding);
+        require!(
+            ctx.accounts.mega_season.challenge_vault != Some(vault.key()),
+            VaultError::MegaChallengePending
+        );
+        require!(
+            !vault.has_metadata || ctx.accounts.vault_metadata.is_some(),
+            VaultError::MissingVaultMetadata
+        );
+
+        let vault_key = vault.key();
+        let vault_id_bytes = vault.vault_id.to_le_bytes();
+        let bump = vault.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault_id_bytes.as_ref(), &[bump]]];
+        let creator = ctx.accounts.creator.to_account_info();
+        let vault_info = ctx.accounts.vault.to_account_info();
+
+        if vault.is_sol_fee {
+            require!(lamports_above_rent(&vault_info)? == 0, VaultError::EscrowNotEmpty);
+        } else {
+            let (Some(fee_mint), Some(vault_fee_ata), Some(token_program)) = (
+                ctx.accounts.fee_mint.as_ref(),
+                ctx.accounts.vault_fee_ata.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(VaultError::WrongFeeCurrency);
+            };
+            require_keys_eq!(fee_mint.key(), vault.fee_mint, VaultError::WrongFeeMint);
+            require!(vault_fee_ata.amount == 0, VaultError::EscrowNotEmpty);
+            close_vault_token_account(
+                token_program,
+                vault_fee_ata.to_account_info(),
+                &creator,
+                &vault_info,
+                signer_seeds,
+            )?;
+        }
+
+        let mut closed: u32 = 0;
+        for group in ctx.remaining_accounts.chunks_exact(3) {
+            let reward = Account::<VaultReward>::try_from(&group[0])?;
+            let vault_reward_ata = InterfaceAccount::<ITokenAccount>::try_from(&group[1])?;
+            let token_program = Interface::<TokenInterface>::try_from(&group[2])?;
+
+            require_keys_eq!(reward.vault, vault_key, VaultError::RewardWrongVault);
+            require_keys_eq!(reward.token_program, token_program.key(), VaultError::RewardWrongTokenProgram);
+            require!(reward.claimed && reward.amount == 0, VaultError::RewardsOutstanding);
+            require_keys_eq!(
+                vault_reward_ata.key(),
+                get_associated_token_address_with_program_id(&vault_key, &reward.mint, &reward.token_program),
+                VaultError::RewardWrongVault
+            );
+            require!(vault_reward_ata.amount == 0, VaultError::EscrowNotEmpty);
+
+            close_vault_token_account(
+                &token_program,
+                vault_reward_ata.to_account_info(),
+                &creator,
+                &vault_info,
+                signer_seeds,
+            )?;
+            reward.close(creator.clone())?;
+            closed += 1;
+        }
+        require!(
+            ctx.remaining_accounts.len() == closed as usize * 3 && closed == ctx.accounts.vault.reward_accounts,
+            VaultError::RewardsOutstanding
+        );
+
+        ctx.accounts
+            .vault_index
+            .load_mut()?
+            .set_status(ctx.accounts.vault.vault_id, VaultIndexPage::CLOSED);
+
+        emit!(VaultClosed {
+            vault: vault_key,
+            creator: creator.key(),
+            rewards_closed: closed,
+        });
+
+        // The Vault account itself is closed to the creator by the `close` constraint.
         Ok(())
     }
 
     /// Winner claims a single extra reward after the vault expires.
     pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
         let now = Clock::get()?.unix_timestamp;
-        require!(now > ctx.accounts.vault.end_ts, VaultError::VaultNotExpired);
+        ctx.accounts.vault.check_claims_closed(now)?;
         require!(ctx.accounts.vault.winner == Some(ctx.accounts.winner.key()), VaultError::NotWinner);
 
         let reward = &mut ctx.accounts.reward;
@@ -560,14 +1928,24 @@ pub mod vault_game {
 
         reward.amount = 0;
         reward.claimed = true;
+        ctx.accounts.vault.reward_escrows = ctx.accounts.vault.reward_escrows.saturating_sub(1);
+        ctx.accounts.global_stats.record_reward_claimed();
+
+        emit!(RewardClaimed {
+            vault: ctx.accounts.vault.key(),
+            mint: reward.mint,
+            to: ctx.accounts.winner.key(),
+            amount,
+        });
 
         Ok(())
     }
 
     /// Creator reclaims a single extra reward after expiry if nobody won.
     pub fn reclaim_reward(ctx: Context<ReclaimReward>) -> Result<()> {
+        ctx.accounts.global_config.check_not_paused(PAUSE_WITHDRAW)?;
         let now = Clock::get()?.unix_timestamp;
-        require!(now > ctx.accounts.vault.end_ts, VaultError::VaultNotExpired);
+        ctx.accounts.vault.check_claims_closed(now)?;
         require!(ctx.accounts.vault.winner.is_none(), VaultError::AlreadyHasWinner);
         require!(ctx.accounts.vault.creator == ctx.accounts.creator.key(), VaultError::NotCreator);
 
@@ -597,47 +1975,433 @@ pub mod vault_game {
 
         reward.amount = 0;
         reward.claimed = true;
+        ctx.accounts.vault.reward_escrows = ctx.accounts.vault.reward_escrows.saturating_sub(1);
+        ctx.accounts.global_stats.record_reward_reclaimed();
+
+        emit!(RewardClaimed {
+            vault: ctx.accounts.vault.key(),
+            mint: reward.mint,
+            to: ctx.accounts.creator.key(),
+            amount,
+        });
 
         Ok(())
     }
 }
 
-fn next_fee(prev_fee: u64) -> Result<u64> {
-    // ceil(prev_fee * 1.2) == ceil(prev_fee * 6 / 5)
-    let n = prev_fee.checked_mul(6).ok_or(VaultError::MathOverflow)?;
-    Ok((n + 4) / 5)
+/// Validate `args`, write them to the vault's metadata account and mark the vault as having one.
+fn write_vault_metadata(
+    vault: &mut Account<Vault>,
+    metadata: &mut Account<VaultMetadata>,
+    args: VaultMetadataArgs,
+    bump: u8,
+) -> Result<()> {
+    args.validate()?;
+    vault.has_metadata = true;
+
+    metadata.vault = vault.key();
+    metadata.title = args.title;
+    metadata.description = args.description;
+    metadata.category = args.category;
+    metadata.uri = args.uri;
+    metadata.content_hash = args.content_hash;
+    metadata.updated_ts = Clock::get()?.unix_timestamp;
+    metadata.bump = bump;
+
+    emit!(VaultMetadataUpdated {
+        vault: metadata.vault,
+        creator: vault.creator,
+        title: metadata.title.clone(),
+        category: metadata.category.clone(),
+        uri: metadata.uri.clone(),
+        content_hash: metadata.content_hash,
+    });
+
+    Ok(())
 }
 
-fn split_fee(fee: u64) -> Result<(u64, u64)> {
-    // v1 economics: 80% -> vault pool (winner), 20% -> mega vault
-    let winner_cut = fee
-        .checked_mul(80)
-        .ok_or(VaultError::MathOverflow)?
-        / 100;
-    let mega_cut = fee.checked_sub(winner_cut).ok_or(VaultError::MathOverflow)?;
-    Ok((winner_cut, mega_cut))
-}
+/// Shared vault setup for `create_vault` / `create_vault_sol` (fee currency is set by the caller).
+fn init_vault(
+    vault: &mut Vault,
+    args: &CreateVaultArgs,
+    creator: Pubkey,
+    vault_id: u64,
+    bump: u8,
+    config: &GlobalConfig,
+) -> Result<()> {
+    // Record vault id used for PDA signing.
+    vault.vault_id = vault_id;
+
+    // Prize lock rules
+    if args.prize_amount > 0 {
+        require!(args.prize_amount >= 1000, VaultError::PrizeTooSmall);
+    }
 
-// -----------------
-// Args
-// -----------------
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct CreateVaultArgs {
-    pub end_ts: i64,
-    pub secret_hash: [u8; 32],
+    vault.creator = creator;
+    vault.status = VaultStatus::Active as u8;
+    vault.created_at = Clock::get()?.unix_timestamp;
+    vault.end_ts = args.end_ts;
+    vault.secret_hash = args.secret_hash;
+    // Unsalted PINs can be brute-forced offline, so new vaults must be salted. Claim-mode players
+    // commit before expiry and reveal once `reveal_salt` publishes the salt.
+    require!(args.secret_scheme == SecretScheme::SaltedSha256 as u8, VaultError::BadSecretScheme);
+    vault.secret_scheme = args.secret_scheme;
+    vault.salt_revealed = false;
+    vault.salt = [0u8; 32];
+    vault.salt_revealed_at = 0;
+    vault.claim_window_slots = args.claim_window_slots;
+    require!(args.excluded_wallets.len() <= MAX_EXCLUDED_WALLETS, VaultError::TooManyExcludedWallets);
+    vault.excluded_wallets = [Pubkey::default(); MAX_EXCLUDED_WALLETS];
+    vault.excluded_wallets[..args.excluded_wallets.len()].copy_from_slice(&args.excluded_wallets);
+    vault.excluded_wallet_count = args.excluded_wallets.len() as u8;
+    require!(
+        args.settlement_mode == SettlementMode::Claim as u8 || args.settlement_mode == SettlementMode::Referee as u8,
+        VaultError::WrongSettlementMode
+    );
+    vault.settlement_mode = args.settlement_mode;
+    vault.hint_root = args.hint_root;
+    vault.hint_positions_opened = 0;
+    vault.has_metadata = false;
+    vault.prize_amount = args.prize_amount;
+    vault.paid_out = false;
+    vault.mega_paid_out = false;
+
+    // Guess fee ladder (attempts-only): the vault's `FeeCurve` (default 1.2x per attempt).
+    // Starting fee is derived from creator base fee and PIN length.
+    // v1: standard vaults are 3–6 digits. Mega vault uses 8 digits.
+    require!((3..=6).contains(&args.pin_len) || args.pin_len == 8, VaultError::BadPinLen);
+    vault.pin_len = args.pin_len;
+
+    let starting = fees::starting_fee(args.base_fee_amount, args.pin_len).ok_or(VaultError::MathOverflow)?;
+
+    let curve = args.fee_curve.unwrap_or(fees::DEFAULT_FEE_CURVE);
+    let bounds = config.fee_curve_bounds.unwrap_or(FeeCurveBounds::DEFAULT);
+    require!(bounds.allows(&curve, starting), VaultError::BadFeeCurve);
+    vault.fee_curve = curve;
+
+    // Fee cap: per-vault, else the global default, else uncapped (the ladder still saturates).
+    let max_fee = args
+        .max_fee_amount
+        .or(config.default_max_fee_amount)
+        .unwrap_or(u64::MAX);
+    require!(max_fee >= starting, VaultError::BadFee);
+    vault.max_fee_amount = max_fee;
+
+    // Fee split: global split unless the creator overrides it within the admin bounds.
+    let split = match args.fee_split {
+        Some(split) => {
+            let bounds = config.fee_split_bounds.unwrap_or(FeeSplitBounds::DEFAULT);
+            require!(bounds.allows(&split), VaultError::BadFeeSplit);
+            split
+        }
+        None => config.fee_split.unwrap_or(FeeSplit::DEFAULT),
+    };
+    vault.fee_split = split;
+
+    vault.reward_escrows = 0;
+    vault.reward_accounts = 0;
+    vault.rewards_untracked = false;
+    vault.max_attempts_per_player = args.max_attempts_per_player;
+    vault.cooldown_secs = args.cooldown_secs;
+
+    // Access gate.
+    vault.access_mode = args.access_mode;
+    vault.gate_mint = Pubkey::default();
+    vault.gate_min_amount = 0;
+    vault.allowlist_root = [0u8; 32];
+    match args.access_mode {
+        m if m == AccessMode::Open as u8 => {}
+        m if m == AccessMode::TokenGated as u8 => {
+            require!(args.gate_mint.is_some() && args.gate_min_amount > 0, VaultError::BadAccessMode);
+            vault.gate_mint = args.gate_mint.unwrap_or_default();
+            vault.gate_min_amount = args.gate_min_amount;
+        }
+        m if m == AccessMode::Allowlist as u8 => {
+            vault.allowlist_root = args.allowlist_root.ok_or(VaultError::BadAccessMode)?;
+        }
+        _ => return err!(VaultError::BadAccessMode),
+    }
 
-    /// Locked prize amount (SKR) held by the vault.
-    /// 0 is allowed for demo/free vaults.
-    pub prize_amount: u64,
+    vault.starting_fee_amount = starting;
+    vault.current_fee_amount = starting;
+    vault.attempt_count = 0;
 
-    /// Base attempt fee chosen by creator. (0 = free vault)
-    pub base_fee_amount: u64,
+    vault.total_fees_collected = 0;
+    vault.winner_fee_pool = 0;
 
-    /// Numeric PIN length (3–6). Used to scale the starting attempt cost.
-    pub pin_len: u8,
+    vault.winner = None;
+    vault.settled_at = None;
+    vault.claim_slot = 0;
+    vault.bump = bump;
 
-    /// Fee mint. v1: must be Some(GlobalState.skr_mint) for token vaults.
-    pub fee_mint: Option<Pubkey>,
+    Ok(())
+}
+
+/// Lamports a program-owned account holds above its rent-exempt minimum.
+fn lamports_above_rent(info: &AccountInfo) -> Result<u64> {
+    let min = Rent::get()?.minimum_balance(info.data_len());
+    Ok(info.lamports().saturating_sub(min))
+}
+
+/// `transfer_checked` of a fee/prize token; returns what `to` actually received.
+///
+/// Token-2022 mints with the transfer-fee extension withhold part of `amount` in `to`, so the
+/// credited amount is measured from `to`'s balance rather than assumed.
+fn transfer_fee_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, IMint>,
+    from: AccountInfo<'info>,
+    to: &mut InterfaceAccount<'info, ITokenAccount>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let before = to.amount;
+    let cpi = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from,
+            mint: mint.to_account_info(),
+            to: to.to_account_info(),
+            authority,
+        },
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi, amount, mint.decimals)?;
+    to.reload()?;
+    to.amount.checked_sub(before).ok_or(VaultError::MathOverflow.into())
+}
+
+/// `load_init` for an account `init_if_needed` just created, `load_mut` otherwise.
+fn load_or_init<'a, T: ZeroCopy + Owner>(loader: &'a AccountLoader<'_, T>) -> Result<std::cell::RefMut<'a, T>> {
+    let fresh = loader.as_ref().try_borrow_data()?[..8].iter().all(|b| *b == 0);
+    if fresh {
+        loader.load_init()
+    } else {
+        loader.load_mut()
+    }
+}
+
+/// Record a freshly created vault in the global page and its creator's page.
+fn index_new_vault(
+    vault_index: &AccountLoader<VaultIndexPage>,
+    vault_index_bump: u8,
+    creator_index: &AccountLoader<CreatorVaultIndex>,
+    creator_index_bump: u8,
+    vault: &Vault,
+    creator_seq: u64,
+) -> Result<()> {
+    let mut page = load_or_init(vault_index)?;
+    page.init(VaultIndexPage::page_of(vault.vault_id), vault_index_bump);
+    page.insert(vault.vault_id, vault.end_ts, vault.is_sol_fee);
+    page.set_status(vault.vault_id, vault.status);
+
+    let mut mine = load_or_init(creator_index)?;
+    mine.creator = vault.creator;
+    mine.page = CreatorVaultIndex::page_of(creator_seq);
+    mine.bump = creator_index_bump;
+    mine.push(vault.vault_id)
+}
+
+/// Close a vault-owned token account, sending its rent to `destination`.
+fn close_vault_token_account<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    account: AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account,
+            destination: destination.clone(),
+            authority: vault.clone(),
+        },
+        signer_seeds,
+    );
+    token_interface::close_account(cpi)
+}
+
+/// Close a won mega season after paying `payout`, and open the next one with the remainder.
+/// Returns the closed season's id.
+fn close_won_season(
+    season: &mut MegaSeason,
+    next: &mut MegaSeason,
+    winner: Pubkey,
+    payout: u64,
+    now: i64,
+    next_bump: u8,
+) -> Result<u64> {
+    let closing_balance = season.jackpot()?.saturating_sub(payout);
+    season.status = MegaSeasonStatus::Won as u8;
+    season.winner = Some(winner);
+    season.payout = payout;
+    season.closing_balance = closing_balance;
+    season.closed_at = Some(now);
+
+    next.open(season.fee_mint, season.season_id + 1, closing_balance, now, next_bump);
+
+    emit!(MegaSeasonOpened {
+        fee_mint: season.fee_mint,
+        season_id: season.season_id + 1,
+        opening_balance: closing_balance,
+    });
+
+    Ok(season.season_id)
+}
+
+/// Mega challenge payout out of `pool`, per `GlobalConfig.mega_payout_bps` (whole pool if unset).
+fn mega_payout(pool: u64, payout_bps: Option<u16>) -> u64 {
+    match payout_bps {
+        Some(bps) => (pool as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64,
+        None => pool,
+    }
+}
+
+/// Message the referee signs for `submit_verdict`: vault || player || attempt_index (LE) || verdict.
+pub fn verdict_message(vault: &Pubkey, player: &Pubkey, attempt_index: u64, verdict: u8) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(32 + 32 + 8 + 1);
+    msg.extend_from_slice(vault.as_ref());
+    msg.extend_from_slice(player.as_ref());
+    msg.extend_from_slice(&attempt_index.to_le_bytes());
+    msg.push(verdict);
+    msg
+}
+
+/// Check that `ix` is an Ed25519 program instruction with a single signature by `signer` over `msg`.
+///
+/// All offsets must point into the instruction's own data (index `u16::MAX`), otherwise the
+/// verified key/message could live in a different instruction than the one we inspect.
+fn verify_ed25519_ix(ix: &Instruction, signer: &Pubkey, msg: &[u8]) -> Result<()> {
+    require_keys_eq!(ix.program_id, solana_sdk_ids::ed25519_program::ID, VaultError::BadVerdictSignature);
+    require!(ix.accounts.is_empty(), VaultError::BadVerdictSignature);
+
+    // Layout: num_signatures(u8) + padding(u8) + 7 x u16 offsets, then the payload.
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, VaultError::BadVerdictSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+
+    let sig_ix_index = read_u16(4);
+    let pubkey_offset = read_u16(6) as usize;
+    let pubkey_ix_index = read_u16(8);
+    let msg_offset = read_u16(10) as usize;
+    let msg_size = read_u16(12) as usize;
+    let msg_ix_index = read_u16(14);
+    require!(
+        sig_ix_index == u16::MAX && pubkey_ix_index == u16::MAX && msg_ix_index == u16::MAX,
+        VaultError::BadVerdictSignature
+    );
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(VaultError::BadVerdictSignature)?;
+    require!(signed_pubkey == signer.as_ref(), VaultError::BadVerdictSignature);
+
+    let signed_msg = data
+        .get(msg_offset..msg_offset + msg_size)
+        .ok_or(VaultError::BadVerdictSignature)?;
+    require!(signed_msg == msg, VaultError::BadVerdictSignature);
+
+    Ok(())
+}
+
+/// Referral cut of `mega_share` owed to the player's bound referrer (0 if none or unset).
+fn referral_cut_for(player_profile: &PlayerProfile, config: &GlobalConfig, mega_share: u64) -> u64 {
+    match (player_profile.referrer, config.referral_bps) {
+        (Some(_), Some(bps)) => fees::referral_cut(mega_share, bps),
+        _ => 0,
+    }
+}
+
+/// System transfer of `amount` lamports from a signer; no-op for 0.
+fn pay_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let ix = anchor_lang::solana_program::system_instruction::transfer(from.key, to.key, amount);
+    anchor_lang::solana_program::program::invoke(&ix, &[from.clone(), to.clone(), system_program.clone()])?;
+    Ok(())
+}
+
+// -----------------
+// Args
+// -----------------
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateVaultArgs {
+    pub end_ts: i64,
+    /// Commitment to the PIN; its preimage depends on `secret_scheme`.
+    pub secret_hash: [u8; 32],
+
+    /// Locked prize amount (SKR, or lamports for SOL vaults) held by the vault.
+    /// 0 is allowed for demo/free vaults.
+    pub prize_amount: u64,
+
+    /// Base attempt fee chosen by creator. (0 = free vault)
+    pub base_fee_amount: u64,
+
+    /// Numeric PIN length (3–6). Used to scale the starting attempt cost.
+    pub pin_len: u8,
+
+    /// Fee mint: Some(registered `FeeMintConfig` mint) for token vaults, None for SOL vaults.
+    pub fee_mint: Option<Pubkey>,
+
+    /// `SecretScheme` of `secret_hash`; must be `SaltedSha256`. The salt stays off-chain until `reveal_salt`.
+    pub secret_scheme: u8,
+
+    /// If > 0, a claim must be committed within this many slots of the claimer's last attempt.
+    pub claim_window_slots: u64,
+
+    /// Wallets the creator attests are associated with them; barred from winning (max 8).
+    pub excluded_wallets: Vec<Pubkey>,
+
+    /// `SettlementMode`: players' `commit_claim` / `claim_win`, or the referee's `submit_verdict`.
+    pub settlement_mode: u8,
+
+    /// Optional Merkle root over per-position digit leaves (see `hints`), enabling `submit_hint`.
+    pub hint_root: Option<[u8; 32]>,
+
+    /// Attempt fee curve; `None` keeps the 1.2x ladder. Must fit `GlobalConfig.fee_curve_bounds`.
+    pub fee_curve: Option<FeeCurve>,
+
+    /// Attempt fee cap after which the fee stays flat; `None` uses `GlobalConfig.default_max_fee_amount`.
+    pub max_fee_amount: Option<u64>,
+
+    /// Per-vault fee split override; must fit `GlobalConfig.fee_split_bounds`.
+    pub fee_split: Option<FeeSplit>,
+
+    /// Max attempts per player on this vault (0 = unlimited).
+    pub max_attempts_per_player: u32,
+
+    /// Minimum seconds between a player's attempts on this vault (0 = none).
+    pub cooldown_secs: u32,
+
+    /// `AccessMode`: who may guess and claim.
+    pub access_mode: u8,
+
+    /// Token-gated vaults: mint players must hold, and the minimum balance (> 0).
+    pub gate_mint: Option<Pubkey>,
+    pub gate_min_amount: u64,
+
+    /// Allowlist vaults: Merkle root over allowed wallets (see `access`).
+    pub allowlist_root: Option<[u8; 32]>,
+
+    /// Title, description, category and media URI, written to the `vault_metadata` account.
+    pub metadata: Option<VaultMetadataArgs>,
+}
+
+/// A revealed hint leaf with its Merkle proof.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DigitProof {
+    pub position: u8,
+    pub digit: u8,
+    pub salt: [u8; 32],
+    pub siblings: Vec<[u8; 32]>,
 }
 
 // -----------------
@@ -670,119 +2434,179 @@ pub struct InitializeGlobal<'info> {
 }
 
 #[derive(Accounts)]
-pub struct TouchPlayer<'info> {
+pub struct SetGlobalConfig<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+
     #[account(
         init_if_needed,
-        payer = player,
-        space = 8 + PlayerProfile::LEN,
-        seeds = [b"player", player.key().as_ref()],
+        payer = payer,
+        space = 8 + GlobalConfig::LEN,
+        seeds=[b"config"],
         bump
     )]
-    pub player_profile: Account<'info, PlayerProfile>,
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
+    pub authority: Signer<'info>,
 
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SetMegaChallengeVault<'info> {
-    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
+pub struct SetFeeMintConfig<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
     pub global_state: Account<'info, GlobalState>,
 
+    pub fee_mint: InterfaceAccount<'info, IMint>,
+
     #[account(
         init_if_needed,
-        payer = authority,
-        space = 8 + MegaChallenge::LEN,
-        seeds=[b"mega_challenge"],
+        payer = payer,
+        space = 8 + FeeMintConfig::LEN,
+        seeds=[b"fee_mint", fee_mint.key().as_ref()],
         bump
     )]
-    pub mega_challenge: Account<'info, MegaChallenge>,
+    pub fee_mint_config: Account<'info, FeeMintConfig>,
 
-    #[account(mut)]
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MintStats::LEN,
+        seeds=[b"mint_stats", fee_mint.key().as_ref()],
+        bump
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
     pub authority: Signer<'info>,
 
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(args: CreateVaultArgs)]
-pub struct CreateVault<'info> {
-    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
-    pub global_state: Box<Account<'info, GlobalState>>,
-
-    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
-    pub mega_vault: Box<Account<'info, MegaVault>>,
+pub struct InitGlobalStats<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
 
     #[account(
         init,
-        payer = creator,
-        space = 8 + Vault::LEN,
-        seeds = [b"vault", global_state.vault_count.to_le_bytes().as_ref()],
+        payer = payer,
+        space = 8 + GlobalStats::LEN,
+        seeds = [b"stats"],
         bump
     )]
-    pub vault: Box<Account<'info, Vault>>,
+    pub global_stats: Account<'info, GlobalStats>,
 
     #[account(
-        init_if_needed,
-        payer = creator,
-        space = 8 + PlayerProfile::LEN,
-        seeds = [b"player", creator.key().as_ref()],
+        init,
+        payer = payer,
+        space = 8 + MintStats::LEN,
+        seeds = [b"mint_stats", Pubkey::default().as_ref()],
         bump
     )]
-    pub player_profile: Box<Account<'info, PlayerProfile>>,
+    pub sol_mint_stats: Account<'info, MintStats>,
 
-    // v1: SKR mint (matches global_state.skr_mint)
-    pub fee_mint: Box<Account<'info, Mint>>,
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
+    pub authority: Signer<'info>,
 
-    #[account(
-        mut,
-        constraint = creator_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
-        constraint = creator_fee_ata.owner == creator.key() @ VaultError::WrongFeeOwner
-    )]
-    pub creator_fee_ata: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
 
     #[account(
-        init_if_needed,
-        payer = creator,
-        associated_token::mint = fee_mint,
-        associated_token::authority = vault,
+        init,
+        payer = payer,
+        space = 8 + Treasury::LEN,
+        seeds = [b"treasury"],
+        bump
     )]
-    pub vault_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury: Account<'info, Treasury>,
+
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasurySol<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(mut, seeds=[b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(seeds=[b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    pub fee_mint: InterfaceAccount<'info, IMint>,
 
     #[account(
-        init_if_needed,
-        payer = creator,
+        mut,
         associated_token::mint = fee_mint,
-        associated_token::authority = vault,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
     )]
-    pub vault_prize_ata: Box<Account<'info, TokenAccount>>,
+    pub treasury_fee_ata: InterfaceAccount<'info, ITokenAccount>,
 
     #[account(
-        init_if_needed,
-        payer = creator,
-        associated_token::mint = fee_mint,
-        associated_token::authority = mega_vault,
+        mut,
+        constraint = authority_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = authority_fee_ata.owner == authority.key() @ VaultError::WrongFeeOwner
     )]
-    pub mega_vault_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub authority_fee_ata: InterfaceAccount<'info, ITokenAccount>,
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+    pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 #[derive(Accounts)]
-pub struct MakeGuessSol<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
+pub struct AcceptAuthority<'info> {
+    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
 
-    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
-    pub mega_vault: Account<'info, MegaVault>,
+    #[account(mut, seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Account<'info, GlobalConfig>,
 
+    /// May be a multisig PDA signing via CPI.
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TouchPlayer<'info> {
     #[account(
         init_if_needed,
         payer = player,
@@ -799,13 +2623,8 @@ pub struct MakeGuessSol<'info> {
 }
 
 #[derive(Accounts)]
-pub struct MakeGuessSpl<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
-
-    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
-    pub mega_vault: Account<'info, MegaVault>,
-
+#[instruction(referrer: Pubkey)]
+pub struct BindReferrer<'info> {
     #[account(
         init_if_needed,
         payer = player,
@@ -815,142 +2634,1024 @@ pub struct MakeGuessSpl<'info> {
     )]
     pub player_profile: Account<'info, PlayerProfile>,
 
+    #[account(seeds = [b"player", referrer.as_ref()], bump = referrer_profile.bump)]
+    pub referrer_profile: Account<'info, PlayerProfile>,
+
     #[account(mut)]
     pub player: Signer<'info>,
 
-    pub fee_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
 
-    #[account(
-        mut,
-        constraint = player_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
-        constraint = player_fee_ata.owner == player.key() @ VaultError::WrongFeeOwner
-    )]
-    pub player_fee_ata: Account<'info, TokenAccount>,
+#[derive(Accounts)]
+#[instruction(vault: Pubkey)]
+pub struct SetMegaChallengeVault<'info> {
+    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
 
     #[account(
-        mut,
-        associated_token::mint = fee_mint,
-        associated_token::authority = mega_vault,
+        init_if_needed,
+        payer = payer,
+        space = 8 + MegaChallenge::LEN,
+        seeds=[b"mega_challenge"],
+        bump
     )]
-    pub mega_vault_fee_ata: Account<'info, TokenAccount>,
+    pub mega_challenge: Account<'info, MegaChallenge>,
+
+    #[account(address = vault @ VaultError::NotMegaChallengeVault)]
+    pub challenge_vault: Box<Account<'info, Vault>>,
 
     #[account(
         mut,
-        associated_token::mint = fee_mint,
-        associated_token::authority = vault,
+        constraint = mega_season.fee_mint == challenge_vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
     )]
-    pub vault_fee_ata: Account<'info, TokenAccount>,
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimWin<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
+#[instruction(fee_mint: Pubkey)]
+pub struct OpenMegaSeason<'info> {
+    #[account(seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    #[account(seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
 
     #[account(
-        init_if_needed,
-        payer = player,
-        space = 8 + PlayerProfile::LEN,
-        seeds = [b"player", player.key().as_ref()],
+        init,
+        payer = payer,
+        space = 8 + MegaSeason::LEN,
+        seeds = [b"mega_season", fee_mint.as_ref(), 0u64.to_le_bytes().as_ref()],
         bump
     )]
-    pub player_profile: Account<'info, PlayerProfile>,
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    /// Pool ATA for SPL seasons; omitted for the SOL pool.
+    #[account(
+        constraint = mega_vault_fee_ata.mint == fee_mint @ VaultError::WrongFeeMint,
+        constraint = mega_vault_fee_ata.owner == mega_vault.key() @ VaultError::WrongFeeOwner
+    )]
+    pub mega_vault_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    /// `GlobalState.authority`; may be a multisig PDA signing via CPI.
+    pub authority: Signer<'info>,
 
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimPrize<'info> {
-    #[account(mut)]
-    pub vault: Box<Account<'info, Vault>>,
-
-    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
-    pub mega_vault: Box<Account<'info, MegaVault>>,
-
-    pub fee_mint: Box<Account<'info, Mint>>,
-
+pub struct RollMegaSeason<'info> {
     #[account(
         mut,
-        associated_token::mint = fee_mint,
-        associated_token::authority = vault,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
     )]
-    pub vault_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub mega_season: Box<Account<'info, MegaSeason>>,
 
     #[account(
-        mut,
-        associated_token::mint = fee_mint,
-        associated_token::authority = vault,
+        init,
+        payer = payer,
+        space = 8 + MegaSeason::LEN,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), (mega_season.season_id + 1).to_le_bytes().as_ref()],
+        bump
     )]
-    pub vault_prize_ata: Box<Account<'info, TokenAccount>>,
+    pub next_season: Box<Account<'info, MegaSeason>>,
 
-    #[account(
-        mut,
-        constraint = winner_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
-        constraint = winner_fee_ata.owner == winner.key() @ VaultError::WrongFeeOwner
-    )]
-    pub winner_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub challenge_vault: Box<Account<'info, Vault>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 
     #[account(mut)]
-    pub winner: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ReclaimPrize<'info> {
-    #[account(mut)]
-    pub vault: Box<Account<'info, Vault>>,
-
+#[instruction(args: CreateVaultArgs)]
+pub struct CreateVault<'info> {
+    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", global_state.vault_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", creator.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Box<Account<'info, PlayerProfile>>,
+
+    // Must have an enabled `FeeMintConfig`.
+    pub fee_mint: Box<InterfaceAccount<'info, IMint>>,
+
+    #[account(
+        mut,
+        constraint = creator_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = creator_fee_ata.owner == creator.key() @ VaultError::WrongFeeOwner
+    )]
+    pub creator_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_prize_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = fee_mint,
+        associated_token::authority = mega_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub mega_vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"fee_mint", fee_mint.key().as_ref()], bump = fee_mint_config.bump)]
+    pub fee_mint_config: Box<Account<'info, FeeMintConfig>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<VaultIndexPage>(),
+        seeds = [b"vault_index", VaultIndexPage::page_of(global_state.vault_count).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<CreatorVaultIndex>(),
+        seeds = [
+            b"creator_vaults",
+            creator.key().as_ref(),
+            CreatorVaultIndex::page_of(player_profile.vaults_created).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub creator_index: AccountLoader<'info, CreatorVaultIndex>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// Required when `args.metadata` is set.
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + VaultMetadata::LEN,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_metadata: Option<Box<Account<'info, VaultMetadata>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CreateVaultArgs)]
+pub struct CreateVaultSol<'info> {
+    #[account(mut, seeds=[b"global"], bump = global_state.bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", global_state.vault_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", creator.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Box<Account<'info, PlayerProfile>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<VaultIndexPage>(),
+        seeds = [b"vault_index", VaultIndexPage::page_of(global_state.vault_count).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<CreatorVaultIndex>(),
+        seeds = [
+            b"creator_vaults",
+            creator.key().as_ref(),
+            CreatorVaultIndex::page_of(player_profile.vaults_created).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub creator_index: AccountLoader<'info, CreatorVaultIndex>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// Required when `args.metadata` is set.
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + VaultMetadata::LEN,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_metadata: Option<Box<Account<'info, VaultMetadata>>>,
+}
+
+#[derive(Accounts)]
+pub struct MakeGuessSol<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Account<'info, MegaVault>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + AttemptRecord::LEN,
+        seeds = [b"attempt", vault.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub attempt_record: Account<'info, AttemptRecord>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + GuessRecord::LEN,
+        seeds = [b"guess", vault.key().as_ref(), vault.attempt_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub guess_record: Account<'info, GuessRecord>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    /// Required when the vault's split has a treasury share.
+    #[account(mut, seeds=[b"treasury"], bump = treasury.bump)]
+    pub treasury: Option<Box<Account<'info, Treasury>>>,
+
+    /// Required when the vault's split has a creator share.
+    #[account(mut, address = vault.creator @ VaultError::NotCreator)]
+    pub vault_creator: Option<SystemAccount<'info>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// Bound referrer's profile; required when a referral cut is due.
+    #[account(
+        mut,
+        seeds = [b"player", referrer_profile.authority.as_ref()],
+        bump = referrer_profile.bump,
+        constraint = Some(referrer_profile.authority) == player_profile.referrer @ VaultError::BadReferrer,
+    )]
+    pub referrer_profile: Option<Box<Account<'info, PlayerProfile>>>,
+
+    /// Bound referrer's wallet; required when a referral cut is due.
+    #[account(
+        mut,
+        constraint = Some(referrer.key()) == player_profile.referrer @ VaultError::BadReferrer,
+    )]
+    pub referrer: Option<SystemAccount<'info>>,
+
+    /// Player's token account for the vault's gate mint; required on token-gated vaults.
+    pub gate_token_account: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct MakeGuessSpl<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Account<'info, MegaVault>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + AttemptRecord::LEN,
+        seeds = [b"attempt", vault.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub attempt_record: Account<'info, AttemptRecord>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + GuessRecord::LEN,
+        seeds = [b"guess", vault.key().as_ref(), vault.attempt_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub guess_record: Account<'info, GuessRecord>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub fee_mint: InterfaceAccount<'info, IMint>,
+
+    #[account(
+        mut,
+        constraint = player_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = player_fee_ata.owner == player.key() @ VaultError::WrongFeeOwner
+    )]
+    pub player_fee_ata: InterfaceAccount<'info, ITokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = mega_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub mega_vault_fee_ata: InterfaceAccount<'info, ITokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_fee_ata: InterfaceAccount<'info, ITokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(seeds=[b"fee_mint", fee_mint.key().as_ref()], bump = fee_mint_config.bump)]
+    pub fee_mint_config: Box<Account<'info, FeeMintConfig>>,
+
+    #[account(seeds=[b"treasury"], bump = treasury.bump)]
+    pub treasury: Option<Box<Account<'info, Treasury>>>,
+
+    /// Required when the vault's split has a treasury share.
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    /// Required when the vault's split has a creator share.
+    #[account(
+        mut,
+        constraint = creator_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = creator_fee_ata.owner == vault.creator @ VaultError::WrongFeeOwner
+    )]
+    pub creator_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// Bound referrer's profile; required when a referral cut is due.
+    #[account(
+        seeds = [b"player", referrer_profile.authority.as_ref()],
+        bump = referrer_profile.bump,
+        constraint = Some(referrer_profile.authority) == player_profile.referrer @ VaultError::BadReferrer,
+    )]
+    pub referrer_profile: Option<Box<Account<'info, PlayerProfile>>>,
+
+    /// Bound referrer's fee token account; required when a referral cut is due.
+    #[account(
+        mut,
+        constraint = referrer_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = Some(referrer_fee_ata.owner) == player_profile.referrer @ VaultError::BadReferrer,
+    )]
+    pub referrer_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    /// Bound referrer's earnings in this fee mint; required when a referral cut is due.
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + ReferralEarnings::LEN,
+        seeds = [b"referral", player_profile.referrer.unwrap_or_default().as_ref(), fee_mint.key().as_ref()],
+        bump
+    )]
+    pub referral_earnings: Option<Box<Account<'info, ReferralEarnings>>>,
+
+    /// Player's token account for the vault's gate mint; required on token-gated vaults.
+    pub gate_token_account: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitClaim<'info> {
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + ClaimCommitment::LEN,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub claim_commitment: Account<'info, ClaimCommitment>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWin<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump = claim_commitment.bump,
+        constraint = claim_commitment.vault == vault.key() @ VaultError::ClaimCommitmentMismatch,
+    )]
+    pub claim_commitment: Account<'info, ClaimCommitment>,
+
+    #[account(
+        seeds = [b"attempt", vault.key().as_ref(), player.key().as_ref()],
+        bump = attempt_record.bump,
+    )]
+    pub attempt_record: Account<'info, AttemptRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+
+    /// Profile of the provisional winner; required only when displacing them.
+    #[account(mut)]
+    pub displaced_profile: Option<Account<'info, PlayerProfile>>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Player's token account for the vault's gate mint; required on token-gated vaults.
+    pub gate_token_account: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+}
+
+#[derive(Accounts)]
+#[instruction(attempt_index: u64)]
+pub struct SubmitVerdict<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"guess", vault.key().as_ref(), attempt_index.to_le_bytes().as_ref()],
+        bump = guess_record.bump,
+    )]
+    pub guess_record: Account<'info, GuessRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerProfile::LEN,
+        seeds = [b"player", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: address-checked instructions sysvar, read for the referee's Ed25519 instruction.
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+}
+
+#[derive(Accounts)]
+#[instruction(attempt_index: u64)]
+pub struct SubmitHint<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"guess", vault.key().as_ref(), attempt_index.to_le_bytes().as_ref()],
+        bump = guess_record.bump,
+    )]
+    pub guess_record: Account<'info, GuessRecord>,
+
+    /// Vault creator or the registered referee.
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSalt<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
+
+    pub fee_mint: Box<InterfaceAccount<'info, IMint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_prize_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = winner_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = winner_fee_ata.owner == winner.key() @ VaultError::WrongFeeOwner
+    )]
+    pub winner_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"fee_mint", fee_mint.key().as_ref()], bump = fee_mint_config.bump)]
+    pub fee_mint_config: Box<Account<'info, FeeMintConfig>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimPrize<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
     #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
     pub mega_vault: Box<Account<'info, MegaVault>>,
 
-    pub fee_mint: Box<Account<'info, Mint>>,
+    pub fee_mint: Box<InterfaceAccount<'info, IMint>>,
 
     #[account(
         mut,
         associated_token::mint = fee_mint,
         associated_token::authority = vault,
+        associated_token::token_program = token_program,
     )]
-    pub vault_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = fee_mint,
         associated_token::authority = vault,
+        associated_token::token_program = token_program,
     )]
-    pub vault_prize_ata: Box<Account<'info, TokenAccount>>,
+    pub vault_prize_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
 
     #[account(
         mut,
         constraint = creator_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
         constraint = creator_fee_ata.owner == creator.key() @ VaultError::WrongFeeOwner
     )]
-    pub creator_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub creator_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = fee_mint,
         associated_token::authority = mega_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub mega_vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(seeds=[b"fee_mint", fee_mint.key().as_ref()], bump = fee_mint_config.bump)]
+    pub fee_mint_config: Box<Account<'info, FeeMintConfig>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
     )]
-    pub mega_vault_fee_ata: Box<Account<'info, TokenAccount>>,
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrizeSol<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimPrizeSol<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    // Fee-currency totals; `Pubkey::default()` for SOL vaults.
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMegaVault<'info> {
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(
+        init,
+        payer = winner,
+        space = 8 + MegaSeason::LEN,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), (mega_season.season_id + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
+
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+
+    #[account(address = vault.fee_mint @ VaultError::WrongFeeMint)]
+    pub fee_mint: Box<InterfaceAccount<'info, IMint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = mega_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub mega_vault_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = winner_fee_ata.mint == fee_mint.key() @ VaultError::WrongFeeMint,
+        constraint = winner_fee_ata.owner == winner.key() @ VaultError::WrongFeeOwner
+    )]
+    pub winner_fee_ata: Box<InterfaceAccount<'info, ITokenAccount>>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ClaimMegaVaultSol<'info> {
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(
+        init,
+        payer = winner,
+        space = 8 + MegaSeason::LEN,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), (mega_season.season_id + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(mut, seeds=[b"mega_vault"], bump = mega_vault.bump)]
+    pub mega_vault: Box<Account<'info, MegaVault>>,
+
+    #[account(mut, seeds=[b"mint_stats", vault.fee_mint.as_ref()], bump = mint_stats.bump)]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// CHECK: a pre-upgrade `Vault`; size and discriminator are checked in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePlayerProfile<'info> {
+    /// CHECK: a pre-upgrade `PlayerProfile`; size and discriminator are checked in the handler.
+    #[account(mut, owner = crate::ID)]
+    pub player_profile: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IndexVault<'info> {
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<VaultIndexPage>(),
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// -----------------
+// Vault metadata
+// -----------------
+
+#[derive(Accounts)]
+pub struct SetVaultMetadata<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + VaultMetadata::LEN,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_metadata: Box<Account<'info, VaultMetadata>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
 // -----------------
 // Reward escrow (any SPL / standard NFT)
 // -----------------
@@ -993,6 +3694,12 @@ pub struct AddReward<'info> {
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
 }
 
 #[derive(Accounts)]
@@ -1035,6 +3742,12 @@ pub struct ClaimReward<'info> {
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
 }
 
 #[derive(Accounts)]
@@ -1062,106 +3775,971 @@ pub struct ReclaimReward<'info> {
     )]
     pub vault_reward_ata: InterfaceAccount<'info, ITokenAccount>,
 
-    #[account(
-        init_if_needed,
-        payer = creator,
-        associated_token::mint = reward_mint,
-        associated_token::authority = creator,
-        associated_token::token_program = token_program,
-    )]
-    pub creator_reward_ata: InterfaceAccount<'info, ITokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = reward_mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program,
+    )]
+    pub creator_reward_ata: InterfaceAccount<'info, ITokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVault<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    // SPL vaults only.
+    pub fee_mint: Option<Box<InterfaceAccount<'info, IMint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_prize_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = creator_fee_ata.owner == creator.key() @ VaultError::WrongFeeOwner
+    )]
+    pub creator_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+
+    #[account(mut, seeds=[b"stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut, close = creator)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        constraint = mega_season.fee_mint == vault.fee_mint @ VaultError::WrongMegaSeason,
+        constraint = mega_season.status == MegaSeasonStatus::Open as u8 @ VaultError::WrongMegaSeason,
+        seeds = [b"mega_season", mega_season.fee_mint.as_ref(), mega_season.season_id.to_le_bytes().as_ref()],
+        bump = mega_season.bump,
+    )]
+    pub mega_season: Box<Account<'info, MegaSeason>>,
+
+    #[account(seeds=[b"config"], bump = global_config.bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    // SPL vaults only.
+    pub fee_mint: Option<Box<InterfaceAccount<'info, IMint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_fee_ata: Option<Box<InterfaceAccount<'info, ITokenAccount>>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Required when `vault.has_metadata`; its rent goes back to the creator too.
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"vault_meta", vault.key().as_ref()],
+        bump = vault_metadata.bump
+    )]
+    pub vault_metadata: Option<Box<Account<'info, VaultMetadata>>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_index", VaultIndexPage::page_of(vault.vault_id).to_le_bytes().as_ref()],
+        bump = vault_index.load()?.bump,
+    )]
+    pub vault_index: AccountLoader<'info, VaultIndexPage>,
+}
+
+// -----------------
+// State
+// -----------------
+#[account]
+pub struct GlobalState {
+    pub authority: Pubkey,
+    /// Default (SKR) fee mint shown by clients. Accepted mints live in `FeeMintConfig`.
+    pub skr_mint: Pubkey,
+    pub vault_count: u64,
+    pub bump: u8,
+}
+impl GlobalState {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+
+    /// The one admin check behind every authority-gated instruction.
+    ///
+    /// `authority` only has to be a transaction signer, so a multisig PDA signing via CPI works.
+    pub fn require_admin(&self, authority: &Signer) -> Result<()> {
+        require_keys_eq!(authority.key(), self.authority, VaultError::NotAuthorized);
+        Ok(())
+    }
+}
+
+/// Admin-managed settings. Separate PDA to avoid resizing `GlobalState` on devnet.
+///
+/// Must exist (any `SetGlobalConfig` instruction creates it) before vaults can be created or played,
+/// since every user-facing instruction checks its pause bits.
+#[account]
+pub struct GlobalConfig {
+    /// Ed25519 key whose signed verdicts settle referee-mode vaults.
+    pub referee: Pubkey,
+    /// Share of the mega pool paid to the mega challenge winner; `None` pays the whole pool.
+    pub mega_payout_bps: Option<u16>,
+    /// Limits on creator-chosen fee curves; `None` uses `FeeCurveBounds::DEFAULT`.
+    pub fee_curve_bounds: Option<FeeCurveBounds>,
+    /// Fee cap for vaults created without `max_fee_amount`; `None` = uncapped.
+    pub default_max_fee_amount: Option<u64>,
+    /// Split for vaults created without an override; `None` = `FeeSplit::DEFAULT` (80/20).
+    pub fee_split: Option<FeeSplit>,
+    /// Limits on per-vault split overrides; `None` = `FeeSplitBounds::DEFAULT`.
+    pub fee_split_bounds: Option<FeeSplitBounds>,
+    /// Share of each attempt's mega cut paid to the player's referrer; `None` = no referrals.
+    pub referral_bps: Option<u16>,
+    /// Emergency pause bits (`PAUSE_*`).
+    pub paused: u8,
+    /// Proposed next `GlobalState.authority`, set by `propose_authority` until accepted.
+    pub pending_authority: Option<Pubkey>,
+    pub bump: u8,
+}
+impl GlobalConfig {
+    pub const LEN: usize = 32
+        + (1 + 2)
+        + (1 + FeeCurveBounds::LEN)
+        + (1 + 8)
+        + (1 + FeeSplit::LEN)
+        + (1 + FeeSplitBounds::LEN)
+        + (1 + 2)
+        + 1
+        + (1 + 32)
+        + 1;
+
+    pub fn check_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, VaultError::EmergencyPaused);
+        Ok(())
+    }
+}
+
+/// Protocol treasury PDA `[b"treasury"]`: holds the SOL treasury share and owns the SPL treasury ATAs.
+#[account]
+pub struct Treasury {
+    pub bump: u8,
+}
+impl Treasury {
+    pub const LEN: usize = 1;
+}
+
+/// Registry entry for an accepted SPL fee mint, PDA `[b"fee_mint", mint]`.
+///
+/// Replaces the single `GlobalState.skr_mint`; the mega vault keeps one pool (ATA + seasons) per mint.
+#[account]
+pub struct FeeMintConfig {
+    pub mint: Pubkey,
+    /// New vaults and guesses require `enabled`; settlement of existing vaults does not.
+    pub enabled: bool,
+    /// Minimum locked prize for new vaults (0 still allowed for free/demo vaults).
+    pub min_prize_amount: u64,
+    /// Minimum base attempt fee for new vaults.
+    pub min_base_fee_amount: u64,
+    /// Mint decimals at registration time.
+    pub decimals: u8,
+    pub bump: u8,
+}
+impl FeeMintConfig {
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 1 + 1;
+
+    pub fn check_new_vault(&self, mint: &IMint, args: &CreateVaultArgs) -> Result<()> {
+        require!(self.enabled, VaultError::UnsupportedFeeMint);
+        require!(mint.decimals == self.decimals, VaultError::FeeMintDecimalsMismatch);
+        if args.prize_amount > 0 {
+            require!(args.prize_amount >= self.min_prize_amount, VaultError::PrizeTooSmall);
+        }
+        require!(args.base_fee_amount >= self.min_base_fee_amount, VaultError::BadFee);
+        Ok(())
+    }
+}
+
+/// Separate PDA to avoid resizing `GlobalState` on devnet.
+///
+/// The admin is `GlobalState.authority`. Accounts written before the `authority` copy was dropped
+/// from this struct read it as `vault` until the next `set_mega_challenge_vault`.
+#[account]
+pub struct MegaChallenge {
+    pub vault: Pubkey,
+    pub bump: u8,
+}
+impl MegaChallenge {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[account]
+pub struct MegaVault {
+    pub bump: u8,
+}
+impl MegaVault {
+    pub const LEN: usize = 1;
+}
+
+/// One numbered season of the mega pool for a fee currency (`fee_mint`, default = SOL).
+///
+/// Exactly one season per fee currency is `Open`; it books every inflow until it is won
+/// or its challenge expires uncracked, at which point the balance opens the next season.
+#[account]
+pub struct MegaSeason {
+    pub fee_mint: Pubkey,
+    pub season_id: u64,
+    pub status: u8,
+    pub opening_balance: u64,
+    /// Mega cuts of guess fees (`split_fee`).
+    pub fee_inflows: u64,
+    /// Mega share of unclaimed vault pools (`reclaim_prize*`).
+    pub reclaim_inflows: u64,
+    pub challenge_vault: Option<Pubkey>,
+    pub winner: Option<Pubkey>,
+    pub payout: u64,
+    pub closing_balance: u64,
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    pub bump: u8,
+}
+impl MegaSeason {
+    pub const LEN: usize = 32 + 8 + 1 + 8 + 8 + 8 + (1 + 32) + (1 + 32) + 8 + 8 + 8 + (1 + 8) + 1;
+
+    /// Current jackpot: opening balance plus everything booked this season.
+    pub fn jackpot(&self) -> Result<u64> {
+        self.opening_balance
+            .checked_add(self.fee_inflows)
+            .and_then(|v| v.checked_add(self.reclaim_inflows))
+            .ok_or(VaultError::MathOverflow.into())
+    }
+
+    pub fn book_fee_inflow(&mut self, amount: u64) -> Result<()> {
+        self.fee_inflows = self.fee_inflows.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn book_reclaim_inflow(&mut self, amount: u64) -> Result<()> {
+        self.reclaim_inflows = self.reclaim_inflows.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+        Ok(())
+    }
+
+    fn open(&mut self, fee_mint: Pubkey, season_id: u64, opening_balance: u64, now: i64, bump: u8) {
+        self.fee_mint = fee_mint;
+        self.season_id = season_id;
+        self.status = MegaSeasonStatus::Open as u8;
+        self.opening_balance = opening_balance;
+        self.fee_inflows = 0;
+        self.reclaim_inflows = 0;
+        self.challenge_vault = None;
+        self.winner = None;
+        self.payout = 0;
+        self.closing_balance = 0;
+        self.opened_at = now;
+        self.closed_at = None;
+        self.bump = bump;
+    }
+}
+
+#[account]
+pub struct PlayerProfile {
+    pub authority: Pubkey,
+    pub attempts: u64,
+    pub wins: u64,
+    pub vaults_created: u64,
+    pub score: u64,
+    pub last_seen_ts: i64,
+    pub bump: u8,
+
+    // Fields below are appended after `bump` so existing client decoders keep their offsets.
+
+    // Set once by `bind_referrer`.
+    pub referrer: Option<Pubkey>,
+    // Referral cuts earned as a referrer, in lamports. SPL cuts are kept per mint in
+    // `ReferralEarnings`, since amounts in different fee mints cannot be summed.
+    pub referral_earnings_sol: u64,
+}
+impl PlayerProfile {
+    /// Size before `referrer` was appended; `migrate_player_profile` grows such profiles.
+    pub const LEGACY_LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub const LEN: usize = Self::LEGACY_LEN
+        // referrer
+        + (1 + 32)
+        // referral_earnings_sol
+        + 8;
+}
+
+#[account]
+pub struct Vault {
+    pub creator: Pubkey,
+    pub status: u8,
+    pub created_at: i64,
+    pub end_ts: i64,
+    pub secret_hash: [u8; 32],
+
+    // Vault id used for PDA signing (seeded by global_state.vault_count at creation time)
+    pub vault_id: u64,
+
+    // Locked prize amount as received (net of transfer fees), or lamports above rent for SOL vaults
+    pub prize_amount: u64,
+
+    // Guess fee ladder (attempts-only)
+    pub starting_fee_amount: u64,
+    pub current_fee_amount: u64,
+    pub attempt_count: u64,
+
+    pub is_sol_fee: bool,
+    pub fee_mint: Pubkey,
+
+    pub total_fees_collected: u64,
+    pub winner_fee_pool: u64,
+
+    pub winner: Option<Pubkey>,
+    pub settled_at: Option<i64>,
+
+    // Settlement guard
+    pub paid_out: bool,
+
+    pub bump: u8,
+
+    // Fields below are appended after `bump` so existing client decoders keep their offsets.
+
+    // Commitment slot of the current (provisional) winner's claim.
+    pub claim_slot: u64,
+
+    // Secret commitment scheme (`SecretScheme`) + salt, published by `reveal_salt` after expiry.
+    pub secret_scheme: u8,
+    pub salt_revealed: bool,
+    pub salt: [u8; 32],
+
+    // If > 0, claims must be committed within this many slots of the claimer's last attempt.
+    pub claim_window_slots: u64,
+
+    // Creator-declared associates barred from winning (the creator always is): the first
+    // `excluded_wallet_count` entries. Fixed-size so the fields after it keep fixed offsets.
+    pub excluded_wallets: [Pubkey; MAX_EXCLUDED_WALLETS],
+    pub excluded_wallet_count: u8,
+
+    // How a winner is determined (`SettlementMode`).
+    pub settlement_mode: u8,
+
+    // PIN length + optional Merkle root over per-position digit leaves (verifiable hints).
+    pub pin_len: u8,
+    pub hint_root: Option<[u8; 32]>,
+
+    // Set once this vault (as mega challenge) has paid out the mega pool.
+    pub mega_paid_out: bool,
+
+    // Attempt fee curve stepping `current_fee_amount` (see `fees`).
+    pub fee_curve: FeeCurve,
+
+    // Ladder ceiling; `u64::MAX` when uncapped.
+    pub max_fee_amount: u64,
+
+    // How each attempt fee is divided (resolved at creation).
+    pub fee_split: FeeSplit,
+
+    // Per-player limits (0 = off), enforced against `AttemptRecord`.
+    pub max_attempts_per_player: u32,
+    pub cooldown_secs: u32,
+
+    // Access gate (`AccessMode`) and its parameters.
+    pub access_mode: u8,
+    pub gate_mint: Pubkey,
+    pub gate_min_amount: u64,
+    pub allowlist_root: [u8; 32],
+
+    // `VaultReward` escrows not yet claimed/reclaimed; `cancel_vault` must return all of them.
+    pub reward_escrows: u32,
+
+    // Open `VaultReward` accounts; `close_vault` must close all of them.
+    pub reward_accounts: u32,
+
+    // Set by `migrate_vault`: reward counters started at zero, so cancel/close are refused.
+    pub rewards_untracked: bool,
+
+    // Bitmask of PIN positions whose hint leaf (and so digit) `submit_hint` has made public.
+    pub hint_positions_opened: u8,
+
+    // A `VaultMetadata` PDA exists; `close_vault` must close it too.
+    pub has_metadata: bool,
+
+    // When `reveal_salt` ran; opens the salted `claim_win` window.
+    pub salt_revealed_at: i64,
+}
+impl Vault {
+    // Old len + vault_id(8) + prize_amount(8) + paid_out(1)
+    pub const LEN: usize = (32 + 1 + 8 + 8 + 32) + 8 + 8 + (8 + 8 + 8) + 1 + 32 + 8 + 8 + (1 + 32) + (1 + 8) + 1 + 1
+        // claim_slot
+        + 8
+        // secret_scheme + salt_revealed + salt
+        + 1 + 1 + 32
+        // claim_window_slots
+        + 8
+        // excluded_wallets + excluded_wallet_count
+        + 32 * MAX_EXCLUDED_WALLETS + 1
+        // settlement_mode
+        + 1
+        // pin_len + hint_root
+        + 1 + (1 + 32)
+        // mega_paid_out
+        + 1
+        // fee_curve
+        + FeeCurve::LEN
+        // max_fee_amount
+        + 8
+        // fee_split
+        + FeeSplit::LEN
+        // max_attempts_per_player + cooldown_secs
+        + 4 + 4
+        // access_mode + gate_mint + gate_min_amount + allowlist_root
+        + 1 + 32 + 8 + 32
+        // reward_escrows
+        + 4
+        // reward_accounts
+        + 4
+        // rewards_untracked
+        + 1
+        // hint_positions_opened
+        + 1
+        // has_metadata
+        + 1
+        // salt_revealed_at
+        + 8;
+
+    /// A migrated pre-upgrade vault, with every appended field at its legacy behaviour.
+    pub fn from_legacy(v: LegacyVault) -> Vault {
+        Vault {
+            creator: v.creator,
+            status: v.status,
+            created_at: v.created_at,
+            end_ts: v.end_ts,
+            secret_hash: v.secret_hash,
+            vault_id: v.vault_id,
+            prize_amount: v.prize_amount,
+            starting_fee_amount: v.starting_fee_amount,
+            current_fee_amount: v.current_fee_amount,
+            attempt_count: v.attempt_count,
+            is_sol_fee: v.is_sol_fee,
+            fee_mint: v.fee_mint,
+            total_fees_collected: v.total_fees_collected,
+            winner_fee_pool: v.winner_fee_pool,
+            winner: v.winner,
+            settled_at: v.settled_at,
+            paid_out: v.paid_out,
+            bump: v.bump,
+            claim_slot: 0,
+            secret_scheme: SecretScheme::Sha256Pin as u8,
+            salt_revealed: false,
+            salt: [0u8; 32],
+            claim_window_slots: 0,
+            excluded_wallets: [Pubkey::default(); MAX_EXCLUDED_WALLETS],
+            excluded_wallet_count: 0,
+            settlement_mode: SettlementMode::Claim as u8,
+            // Not recorded before the upgrade; only hint vaults read it.
+            pin_len: 0,
+            hint_root: None,
+            mega_paid_out: false,
+            fee_curve: fees::DEFAULT_FEE_CURVE,
+            max_fee_amount: u64::MAX,
+            fee_split: FeeSplit::DEFAULT,
+            max_attempts_per_player: 0,
+            cooldown_secs: 0,
+            access_mode: AccessMode::Open as u8,
+            gate_mint: Pubkey::default(),
+            gate_min_amount: 0,
+            allowlist_root: [0u8; 32],
+            reward_escrows: 0,
+            reward_accounts: 0,
+            rewards_untracked: true,
+            hint_positions_opened: 0,
+            has_metadata: false,
+            salt_revealed_at: 0,
+        }
+    }
+
+    /// Payouts wait for expiry and, on salted claim-mode vaults, for the `claim_win` window that
+    /// `reveal_salt` opens: until it closes, an older commitment can still displace the winner.
+    pub fn check_claims_closed(&self, now: i64) -> Result<()> {
+        require!(now > self.end_ts, VaultError::VaultNotExpired);
+        if self.settlement_mode == SettlementMode::Claim as u8 && self.secret_scheme == SecretScheme::SaltedSha256 as u8 {
+            require!(self.salt_revealed, VaultError::SaltNotRevealed);
+            let closes_at = self
+                .salt_revealed_at
+                .checked_add(SALTED_CLAIM_WINDOW_SECS)
+                .ok_or(VaultError::MathOverflow)?;
+            require!(now > closes_at, VaultError::ClaimWindowOpen);
+        }
+        Ok(())
+    }
+
+    /// Fee `make_guess_*` charges for the next attempt at `now`.
+    pub fn current_fee(&self, now: i64) -> u64 {
+        fees::charged_fee(&self.fee_curve, self.current_fee_amount, now.saturating_sub(self.created_at))
+    }
+
+    /// Ladder fee `make_guess_*` stores once the current attempt is paid.
+    pub fn next_fee(&self) -> u64 {
+        fees::next_ladder_fee(&self.fee_curve, self.current_fee_amount, self.max_fee_amount)
+    }
+
+    /// Book a paid attempt: `fee` charged, `winner_cut` of it added to the pool, ladder stepped.
+    pub fn record_paid_attempt(&mut self, fee: u64, winner_cut: u64) -> Result<()> {
+        self.total_fees_collected = self.total_fees_collected.checked_add(fee).ok_or(VaultError::MathOverflow)?;
+        self.winner_fee_pool = self
+            .winner_fee_pool
+            .checked_add(winner_cut)
+            .ok_or(VaultError::MathOverflow)?;
+        self.attempt_count = self.attempt_count.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        self.current_fee_amount = self.next_fee();
+        Ok(())
+    }
+
+    /// Enforce `max_attempts_per_player` / `cooldown_secs` against the player's record before an attempt.
+    pub fn check_attempt_limits(&self, record: &AttemptRecord, now: i64) -> Result<()> {
+        if self.max_attempts_per_player > 0 {
+            require!(
+                record.attempts < self.max_attempts_per_player as u64,
+                VaultError::AttemptLimitReached
+            );
+        }
+        if self.cooldown_secs > 0 && record.attempts > 0 {
+            let ready_at = record.last_attempt_ts.saturating_add(self.cooldown_secs as i64);
+            require!(now >= ready_at, VaultError::AttemptCooldownActive);
+        }
+        Ok(())
+    }
+
+    /// Enforce the vault's `AccessMode` for `player`.
+    pub fn check_access(
+        &self,
+        player: &Pubkey,
+        gate_token_account: Option<&InterfaceAccount<ITokenAccount>>,
+        allowlist_proof: &[[u8; 32]],
+    ) -> Result<()> {
+        match self.access_mode {
+            m if m == AccessMode::Open as u8 => Ok(()),
+            m if m == AccessMode::TokenGated as u8 => {
+                let held = gate_token_account
+                    .filter(|ta| ta.owner == *player && ta.mint == self.gate_mint)
+                    .map_or(0, |ta| ta.amount);
+                require!(held >= self.gate_min_amount, VaultError::TokenGateNotMet);
+                Ok(())
+            }
+            m if m == AccessMode::Allowlist as u8 => {
+                require!(
+                    access::verify_proof(&self.allowlist_root, player, allowlist_proof),
+                    VaultError::NotOnAllowlist
+                );
+                Ok(())
+            }
+            _ => err!(VaultError::BadAccessMode),
+        }
+    }
+
+    /// As mega challenge: the season may roll over once the vault expired uncracked, or its winner
+    /// left the jackpot unclaimed for `MEGA_CLAIM_TIMEOUT_SECS`.
+    pub fn check_mega_rollover(&self, now: i64) -> Result<()> {
+        self.check_claims_closed(now)?;
+        if self.winner.is_some() {
+            let deadline = self
+                .end_ts
+                .checked_add(MEGA_CLAIM_TIMEOUT_SECS)
+                .ok_or(VaultError::MathOverflow)?;
+            require!(now > deadline, VaultError::MegaClaimPending);
+        }
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+    /// The creator (or a declared associate) knows the secret and may not win their own vault.
+    pub fn is_self_dealing(&self, who: &Pubkey) -> bool {
+        *who == self.creator || self.excluded_wallets[..self.excluded_wallet_count as usize].contains(who)
+    }
+}
 
-    pub token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+/// `Vault` as laid out before fields were appended after `bump`; read only by `migrate_vault`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyVault {
+    pub creator: Pubkey,
+    pub status: u8,
+    pub created_at: i64,
+    pub end_ts: i64,
+    pub secret_hash: [u8; 32],
+    pub vault_id: u64,
+    pub prize_amount: u64,
+    pub starting_fee_amount: u64,
+    pub current_fee_amount: u64,
+    pub attempt_count: u64,
+    pub is_sol_fee: bool,
+    pub fee_mint: Pubkey,
+    pub total_fees_collected: u64,
+    pub winner_fee_pool: u64,
+    pub winner: Option<Pubkey>,
+    pub settled_at: Option<i64>,
+    pub paid_out: bool,
+    pub bump: u8,
+}
+impl LegacyVault {
+    pub const LEN: usize =
+        (32 + 1 + 8 + 8 + 32) + 8 + 8 + (8 + 8 + 8) + 1 + 32 + 8 + 8 + (1 + 32) + (1 + 8) + 1 + 1;
 }
 
-// -----------------
-// State
-// -----------------
+/// Per-player, per-vault attempt ledger written by `make_guess_*`; proves the right to claim.
 #[account]
-pub struct GlobalState {
-    pub authority: Pubkey,
-    pub skr_mint: Pubkey,
-    pub vault_count: u64,
+pub struct AttemptRecord {
+    pub vault: Pubkey,
+    pub player: Pubkey,
+    pub attempts: u64,
+    pub fees_paid: u64,
+    pub last_attempt_slot: u64,
     pub bump: u8,
+    /// Unix time of the last attempt; drives `Vault.cooldown_secs`.
+    pub last_attempt_ts: i64,
 }
-impl GlobalState {
-    pub const LEN: usize = 32 + 32 + 8 + 1;
+impl AttemptRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 1 + 8;
 }
 
-/// Separate PDA to avoid resizing `GlobalState` on devnet.
+/// One attempt on a vault, keyed by vault + attempt index.
 #[account]
-pub struct MegaChallenge {
-    pub authority: Pubkey,
+pub struct GuessRecord {
     pub vault: Pubkey,
+    pub player: Pubkey,
+    pub attempt_index: u64,
+    /// Hashed or encrypted PIN guess supplied by the player.
+    pub guess: [u8; 32],
+    pub fee_paid: u64,
+    pub slot: u64,
+    /// Referee/creator feedback, filled in after the guess.
+    pub feedback: GuessFeedback,
     pub bump: u8,
 }
-impl MegaChallenge {
-    pub const LEN: usize = 32 + 32 + 1;
+impl GuessRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 8 + 8 + GuessFeedback::LEN + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuessFeedback {
+    /// `Verdict` from the referee (`submit_verdict`).
+    pub verdict: Option<u8>,
+    /// Proven Mastermind counts (`submit_hint`).
+    pub hint: Option<HintCounts>,
+}
+impl GuessFeedback {
+    pub const LEN: usize = (1 + 1) + (1 + HintCounts::LEN);
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HintCounts {
+    /// Right digit, right position.
+    pub exact: u8,
+    /// Right digit, wrong position.
+    pub partial: u8,
+}
+impl HintCounts {
+    pub const LEN: usize = 1 + 1;
 }
 
+/// Per-player claim commitment (commit/reveal), closed to the player on reveal.
 #[account]
-pub struct MegaVault {
+pub struct ClaimCommitment {
+    pub vault: Pubkey,
+    pub player: Pubkey,
+    /// sha256(secret || player || nonce)
+    pub commitment: [u8; 32],
+    pub slot: u64,
     pub bump: u8,
 }
-impl MegaVault {
-    pub const LEN: usize = 1;
+impl ClaimCommitment {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
 }
 
+/// Protocol-wide counters for dashboards, PDA [b"stats"]. Kept out of `GlobalState` so fields can be
+/// appended (with a realloc) without touching the accounts every instruction reads.
+///
+/// Counters saturate rather than fail, and only cover activity since `init_global_stats`.
 #[account]
-pub struct PlayerProfile {
-    pub authority: Pubkey,
-    pub attempts: u64,
-    pub wins: u64,
+pub struct GlobalStats {
+    /// `VaultCreated` events.
     pub vaults_created: u64,
-    pub score: u64,
-    pub last_seen_ts: i64,
+    /// Created minus settled / cancelled vaults.
+    pub active_vaults: u64,
+    /// `GuessMade` events, free attempts included.
+    pub total_attempts: u64,
+    /// `RewardAdded` events.
+    pub rewards_added: u64,
+    /// `RewardClaimed` events paid to winners.
+    pub rewards_claimed: u64,
+    /// `RewardClaimed` events returned to creators.
+    pub rewards_reclaimed: u64,
     pub bump: u8,
 }
-impl PlayerProfile {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 1;
+impl GlobalStats {
+    pub const LEN: usize = 8 * 6 + 1;
+
+    fn record_vault_created(&mut self) {
+        self.vaults_created = self.vaults_created.saturating_add(1);
+        self.active_vaults = self.active_vaults.saturating_add(1);
+    }
+
+    /// A vault left `VaultStatus::Active` (won, reclaimed or cancelled).
+    fn record_vault_settled(&mut self) {
+        self.active_vaults = self.active_vaults.saturating_sub(1);
+    }
+
+    fn record_attempt(&mut self) {
+        self.total_attempts = self.total_attempts.saturating_add(1);
+    }
+
+    fn record_reward_added(&mut self) {
+        self.rewards_added = self.rewards_added.saturating_add(1);
+    }
+
+    fn record_reward_claimed(&mut self) {
+        self.rewards_claimed = self.rewards_claimed.saturating_add(1);
+    }
+
+    fn record_reward_reclaimed(&mut self) {
+        self.rewards_reclaimed = self.rewards_reclaimed.saturating_add(1);
+    }
 }
 
+/// Totals for one fee currency, PDA [b"mint_stats", mint] (`Pubkey::default()` for SOL).
+/// Amounts are in the mint's base units and match the corresponding event fields.
 #[account]
-pub struct Vault {
-    pub creator: Pubkey,
-    pub status: u8,
-    pub created_at: i64,
+pub struct MintStats {
+    pub mint: Pubkey,
+    /// Sum of `GuessMade.fee`.
+    pub fees_collected: u64,
+    /// Sum of `PrizeClaimed.amount` and `MegaVaultWon.amount`.
+    pub prizes_paid: u64,
+    /// Sum of `PrizeReclaimed.amount`.
+    pub reclaimed: u64,
+    /// Sum of `GuessMade.mega_cut` and `PrizeReclaimed.mega_cut`.
+    pub mega_inflow: u64,
+    pub bump: u8,
+}
+impl MintStats {
+    pub const LEN: usize = 32 + 8 * 4 + 1;
+
+    fn record_fee(&mut self, fee: u64, mega_cut: u64) {
+        self.fees_collected = self.fees_collected.saturating_add(fee);
+        self.mega_inflow = self.mega_inflow.saturating_add(mega_cut);
+    }
+
+    fn record_prize(&mut self, amount: u64) {
+        self.prizes_paid = self.prizes_paid.saturating_add(amount);
+    }
+
+    fn record_reclaim(&mut self, amount: u64, mega_cut: u64) {
+        self.reclaimed = self.reclaimed.saturating_add(amount);
+        self.mega_inflow = self.mega_inflow.saturating_add(mega_cut);
+    }
+}
+
+/// A referrer's SPL referral cuts in one fee mint, PDA [b"referral", referrer, mint].
+/// `earned` is the sum of `GuessMade.referral_cut` for that referrer and mint.
+#[account]
+pub struct ReferralEarnings {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    pub earned: u64,
+    pub bump: u8,
+}
+impl ReferralEarnings {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+/// One slot of a `VaultIndexPage`; `status` 0 means no vault indexed there yet.
+#[zero_copy]
+pub struct VaultIndexEntry {
     pub end_ts: i64,
-    pub secret_hash: [u8; 32],
+    /// `VaultStatus`, or `VaultIndexPage::CLOSED` once `close_vault` ran.
+    pub status: u8,
+    pub is_sol_fee: u8,
+    pub _padding: [u8; 6],
+}
 
-    // Vault id used for PDA signing (seeded by global_state.vault_count at creation time)
-    pub vault_id: u64,
+/// Fixed-size page of vault ids `page * ENTRIES .. (page + 1) * ENTRIES`, PDA
+/// [b"vault_index", page LE]. The vault at slot `i` is PDA [b"vault", (page * ENTRIES + i) LE].
+///
+/// Clients list active (or settled) vaults by reading pages `0..=page_of(vault_count - 1)` and
+/// skipping those whose `active_count` (or `settled_count`) is zero.
+#[account(zero_copy)]
+pub struct VaultIndexPage {
+    pub page: u64,
+    pub active_count: u32,
+    /// Settled or cancelled, not yet closed.
+    pub settled_count: u32,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub entries: [VaultIndexEntry; 256],
+}
+impl VaultIndexPage {
+    pub const ENTRIES: u64 = 256;
+    pub const CLOSED: u8 = 4;
 
-    // Locked prize amount (SKR)
-    pub prize_amount: u64,
+    pub fn page_of(vault_id: u64) -> u64 {
+        vault_id / Self::ENTRIES
+    }
 
-    // Guess fee ladder (attempts-only)
-    pub starting_fee_amount: u64,
-    pub current_fee_amount: u64,
-    pub attempt_count: u64,
+    fn init(&mut self, page: u64, bump: u8) {
+        self.page = page;
+        self.bump = bump;
+    }
 
-    pub is_sol_fee: bool,
-    pub fee_mint: Pubkey,
+    fn insert(&mut self, vault_id: u64, end_ts: i64, is_sol_fee: bool) {
+        let entry = &mut self.entries[(vault_id % Self::ENTRIES) as usize];
+        entry.end_ts = end_ts;
+        entry.is_sol_fee = is_sol_fee as u8;
+    }
 
-    pub total_fees_collected: u64,
-    pub winner_fee_pool: u64,
+    /// Move a vault between the active / settled views.
+    fn set_status(&mut self, vault_id: u64, status: u8) {
+        let slot = (vault_id % Self::ENTRIES) as usize;
+        match self.entries[slot].status {
+            s if s == VaultStatus::Active as u8 => self.active_count = self.active_count.saturating_sub(1),
+            s if s == VaultStatus::Settled as u8 || s == VaultStatus::Cancelled as u8 => {
+                self.settled_count = self.settled_count.saturating_sub(1)
+            }
+            _ => {}
+        }
+        match status {
+            s if s == VaultStatus::Active as u8 => self.active_count += 1,
+            s if s == VaultStatus::Settled as u8 || s == VaultStatus::Cancelled as u8 => self.settled_count += 1,
+            _ => {}
+        }
+        self.entries[slot].status = status;
+    }
+}
 
-    pub winner: Option<Pubkey>,
-    pub settled_at: Option<i64>,
+/// A creator's vault ids in creation order, PDA [b"creator_vaults", creator, page LE], where page
+/// is `PlayerProfile.vaults_created / IDS` at creation time. Statuses live in `VaultIndexPage`.
+#[account(zero_copy)]
+pub struct CreatorVaultIndex {
+    pub creator: Pubkey,
+    pub page: u64,
+    pub count: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub vault_ids: [u64; 128],
+}
+impl CreatorVaultIndex {
+    pub const IDS: u64 = 128;
 
-    // Settlement guard
-    pub paid_out: bool,
+    pub fn page_of(creator_seq: u64) -> u64 {
+        creator_seq / Self::IDS
+    }
+
+    fn push(&mut self, vault_id: u64) -> Result<()> {
+        let slot = self.count as usize;
+        require!(slot < self.vault_ids.len(), VaultError::IndexPageFull);
+        self.vault_ids[slot] = vault_id;
+        self.count += 1;
+        Ok(())
+    }
+}
 
+/// Human-readable identity of a vault, PDA [b"vault_meta", vault]. Optional: created by the first
+/// `set_vault_metadata`, closed by `close_vault`.
+#[account]
+pub struct VaultMetadata {
+    pub vault: Pubkey,
+    pub title: String,
+    pub description: String,
+    /// Short lowercase tag (`[a-z0-9-]`), e.g. "puzzle".
+    pub category: String,
+    /// Off-chain media / JSON document.
+    pub uri: String,
+    /// sha256 of the document at `uri`, so clients can detect it changing.
+    pub content_hash: [u8; 32],
+    pub updated_ts: i64,
     pub bump: u8,
 }
-impl Vault {
-    // Old len + vault_id(8) + prize_amount(8) + paid_out(1)
-    pub const LEN: usize = (32 + 1 + 8 + 8 + 32) + 8 + 8 + (8 + 8 + 8) + 1 + 32 + 8 + 8 + (1 + 32) + (1 + 8) + 1 + 1;
+impl VaultMetadata {
+    pub const MAX_TITLE_LEN: usize = 64;
+    pub const MAX_DESCRIPTION_LEN: usize = 512;
+    pub const MAX_CATEGORY_LEN: usize = 16;
+    pub const MAX_URI_LEN: usize = 200;
+
+    // Strings are Borsh `u32 len + bytes`; space is allocated for the maximum lengths.
+    pub const LEN: usize = 32
+        + (4 + Self::MAX_TITLE_LEN)
+        + (4 + Self::MAX_DESCRIPTION_LEN)
+        + (4 + Self::MAX_CATEGORY_LEN)
+        + (4 + Self::MAX_URI_LEN)
+        + 32
+        + 8
+        + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VaultMetadataArgs {
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+}
+impl VaultMetadataArgs {
+    /// Lengths are in bytes. Borsh already rejects invalid UTF-8; control characters are refused
+    /// here (newlines are allowed in the description only).
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.title.trim().is_empty()
+                && self.title.len() <= VaultMetadata::MAX_TITLE_LEN
+                && self.description.len() <= VaultMetadata::MAX_DESCRIPTION_LEN
+                && self.category.len() <= VaultMetadata::MAX_CATEGORY_LEN
+                && self.uri.len() <= VaultMetadata::MAX_URI_LEN,
+            VaultError::BadMetadataLength
+        );
+        require!(
+            !self.title.chars().any(char::is_control)
+                && !self.description.chars().any(|c| c.is_control() && c != '\n')
+                && !self.uri.chars().any(|c| c.is_control() || c.is_whitespace())
+                && self
+                    .category
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'),
+            VaultError::BadMetadataText
+        );
+        Ok(())
+    }
 }
 
 /// Extra rewards (any SPL mint / standard NFT) escrowed inside a vault.
@@ -1189,6 +4767,47 @@ pub enum VaultStatus {
     Cancelled = 3,
 }
 
+#[repr(u8)]
+pub enum MegaSeasonStatus {
+    Open = 1,
+    Won = 2,
+    RolledOver = 3,
+}
+
+/// Who may guess on and claim a vault.
+#[repr(u8)]
+pub enum AccessMode {
+    Open = 0,
+    /// Player must hold at least `gate_min_amount` of `gate_mint`.
+    TokenGated = 1,
+    /// Player must prove membership in `allowlist_root`.
+    Allowlist = 2,
+}
+
+/// How a vault's winner is determined.
+#[repr(u8)]
+pub enum SettlementMode {
+    /// Player commits + reveals the PIN (`commit_claim` / `claim_win`); salted vaults reveal after `reveal_salt`.
+    Claim = 0,
+    /// Registered referee signs a verdict on an attempt (`submit_verdict`).
+    Referee = 1,
+}
+
+#[repr(u8)]
+pub enum Verdict {
+    Incorrect = 0,
+    Correct = 1,
+}
+
+/// How `Vault.secret_hash` commits to the PIN. The claim secret is the hash preimage.
+#[repr(u8)]
+pub enum SecretScheme {
+    /// Legacy: sha256(pin). Brute-forceable offline for short PINs; no longer accepted for new vaults.
+    Sha256Pin = 0,
+    /// sha256(salt || pin), with a 32-byte salt held by the creator/referee until `reveal_salt`.
+    SaltedSha256 = 1,
+}
+
 // -----------------
 // Events
 // -----------------
@@ -1202,13 +4821,134 @@ pub struct VaultCreated {
     pub guess_fee_amount: u64,
 }
 
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityChanged {
+    pub previous: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8,
+}
+
+#[event]
+pub struct VaultMetadataUpdated {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub title: String,
+    pub category: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+}
+
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub rewards_closed: u32,
+}
+
+#[event]
+pub struct VaultCancelled {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    /// Prize returned, in the fee mint's base units (or lamports for SOL vaults).
+    pub prize_returned: u64,
+    pub rewards_returned: u32,
+}
+
 #[event]
 pub struct GuessMade {
     pub vault: Pubkey,
     pub player: Pubkey,
+    pub attempt_index: u64,
+    /// Fee charged to the player.
     pub fee: u64,
+    /// Cuts as credited, i.e. net of any Token-2022 transfer fee.
     pub winner_cut: u64,
     pub mega_cut: u64,
+    pub treasury_cut: u64,
+    pub creator_cut: u64,
+    /// Player's referrer when a referral cut was paid.
+    pub referrer: Option<Pubkey>,
+    /// Taken out of the mega share; `mega_cut` is what remained.
+    pub referral_cut: u64,
+}
+
+#[event]
+pub struct ReferrerBound {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ClaimCommitted {
+    pub vault: Pubkey,
+    pub player: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct HintSubmitted {
+    pub vault: Pubkey,
+    pub attempt_index: u64,
+    pub exact: u8,
+    pub partial: u8,
+}
+
+#[event]
+pub struct SaltRevealed {
+    pub vault: Pubkey,
+    pub salt: [u8; 32],
+    pub pin: Vec<u8>,
+}
+
+#[event]
+pub struct VerdictSubmitted {
+    pub vault: Pubkey,
+    pub player: Pubkey,
+    pub attempt_index: u64,
+    pub verdict: u8,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub vault: Pubkey,
+    pub winner: Pubkey,
+    /// Prize + winner pool received, net of any transfer fee.
+    pub amount: u64,
+}
+
+#[event]
+pub struct PrizeReclaimed {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    /// Prize + creator half of the pool received, net of any transfer fee.
+    pub amount: u64,
+    pub mega_cut: u64,
+}
+
+#[event]
+pub struct RewardAdded {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardClaimed {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    /// Winner on `claim_reward`, creator on `reclaim_reward`.
+    pub to: Pubkey,
+    pub amount: u64,
 }
 
 #[event]
@@ -1217,6 +4957,23 @@ pub struct VaultWon {
     pub winner: Pubkey,
 }
 
+#[event]
+pub struct MegaVaultWon {
+    pub vault: Pubkey,
+    pub winner: Pubkey,
+    /// `Pubkey::default()` for the SOL pool.
+    pub fee_mint: Pubkey,
+    pub season_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MegaSeasonOpened {
+    pub fee_mint: Pubkey,
+    pub season_id: u64,
+    pub opening_balance: u64,
+}
+
 // -----------------
 // Errors
 // -----------------
@@ -1250,7 +5007,7 @@ pub enum VaultError {
     NotWinner,
     #[msg("Not the creator")]
     NotCreator,
-    #[msg("Unsupported fee mint (SKR only for now)")]
+    #[msg("Fee mint is not registered or not enabled")]
     UnsupportedFeeMint,
     #[msg("Wrong fee currency for this vault")]
     WrongFeeCurrency,
@@ -1262,6 +5019,106 @@ pub enum VaultError {
     AlreadyHasWinner,
     #[msg("Incorrect secret")]
     BadSecret,
+    #[msg("Claim reveal too early")]
+    ClaimRevealTooEarly,
+    #[msg("Reveal does not match claim commitment")]
+    ClaimCommitmentMismatch,
+    #[msg("Displaced winner profile missing or mismatched")]
+    BadDisplacedProfile,
+    #[msg("Bad secret commitment scheme")]
+    BadSecretScheme,
+    #[msg("Salt already revealed")]
+    SaltAlreadyRevealed,
+    #[msg("Salt must be revealed first")]
+    SaltNotRevealed,
+    #[msg("Claimer has no attempt on this vault")]
+    NoAttempt,
+    #[msg("Claim window since last attempt elapsed")]
+    ClaimWindowElapsed,
+    #[msg("Creator and declared associates cannot win their own vault")]
+    SelfDealingBlocked,
+    #[msg("Too many excluded wallets")]
+    TooManyExcludedWallets,
+    #[msg("Wrong settlement mode for this vault")]
+    WrongSettlementMode,
+    #[msg("Bad verdict")]
+    BadVerdict,
+    #[msg("Guess record does not belong to this player")]
+    GuessRecordMismatch,
+    #[msg("Feedback already set for this guess")]
+    FeedbackAlreadySet,
+    #[msg("Vault has no hint commitment")]
+    HintsNotEnabled,
+    #[msg("Guess opening does not match the guess record")]
+    BadGuessOpening,
+    #[msg("Invalid hint proof")]
+    BadHintProof,
+    #[msg("Basis points out of range")]
+    BadBps,
+    #[msg("Vault is not the mega challenge vault")]
+    NotMegaChallengeVault,
+    #[msg("Mega season is not the open season for this fee currency")]
+    WrongMegaSeason,
+    #[msg("Fee mint decimals do not match its registry entry")]
+    FeeMintDecimalsMismatch,
+    #[msg("Fee curve outside the allowed bounds")]
+    BadFeeCurve,
+    #[msg("Fee split must sum to 10000 bps and fit the allowed bounds")]
+    BadFeeSplit,
+    #[msg("Treasury or creator account required for this vault's fee split")]
+    MissingFeeRecipient,
+    #[msg("Treasury balance too low")]
+    InsufficientTreasury,
+    #[msg("Invalid referrer")]
+    BadReferrer,
+    #[msg("Referrer already bound")]
+    ReferrerAlreadyBound,
+    #[msg("Referrer accounts required for this player's referral cut")]
+    MissingReferrer,
+    #[msg("Player reached this vault's max attempts")]
+    AttemptLimitReached,
+    #[msg("Player must wait for this vault's cooldown between attempts")]
+    AttemptCooldownActive,
+    #[msg("Bad access mode or gate parameters")]
+    BadAccessMode,
+    #[msg("Player does not hold enough of the vault's gate token")]
+    TokenGateNotMet,
+    #[msg("Player is not on the vault's allowlist")]
+    NotOnAllowlist,
+    #[msg("Vault already has attempts")]
+    VaultHasAttempts,
+    #[msg("All open reward escrows must be returned")]
+    RewardsOutstanding,
+    #[msg("Paused by the authority")]
+    EmergencyPaused,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Vault has not been paid out")]
+    VaultNotPaidOut,
+    #[msg("Escrowed balance remains")]
+    EscrowNotEmpty,
+    #[msg("Vault is the unpaid mega challenge")]
+    MegaChallengePending,
+    #[msg("Vault metadata can no longer be edited")]
+    MetadataLocked,
+    #[msg("Vault metadata field is empty or too long")]
+    BadMetadataLength,
+    #[msg("Vault metadata contains invalid characters")]
+    BadMetadataText,
+    #[msg("Index page is full")]
+    IndexPageFull,
+    #[msg("Account is not a pre-upgrade vault")]
+    NotLegacyVault,
+    #[msg("Vault predates reward tracking")]
+    RewardsUntracked,
+    #[msg("Missing or invalid referee verdict signature")]
+    BadVerdictSignature,
+    #[msg("Hint leaf digit does not appear in the guess")]
+    HintDigitNotGuessed,
+    #[msg("Hints may not open every PIN position")]
+    HintLimitReached,
+    #[msg("Hint salts do not rebuild the vault's hint root from the PIN")]
+    BadHintRoot,
 
     #[msg("Reward already claimed")]
     RewardAlreadyClaimed,
@@ -1271,4 +5128,14 @@ pub enum VaultError {
     RewardWrongMint,
     #[msg("Reward token program mismatch")]
     RewardWrongTokenProgram,
+    #[msg("Account is not a pre-upgrade player profile")]
+    NotLegacyProfile,
+    #[msg("Vault metadata account must be passed")]
+    MissingVaultMetadata,
+    #[msg("Claim reveal window is still open")]
+    ClaimWindowOpen,
+    #[msg("Mega winner may still claim the jackpot")]
+    MegaClaimPending,
+    #[msg("Vault metadata account passed without metadata")]
+    UnexpectedVaultMetadata,
 }
@@ -0,0 +1,167 @@
+//! Claim-mode settlement of salted vaults: commit before expiry, reveal once `reveal_salt` has run.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use vault_game::{accounts, instruction, ClaimCommitment, PlayerProfile, SettlementMode, Vault, VaultError};
+
+const PIN: &[u8] = b"4821";
+const SALT: [u8; 32] = [9; 32];
+const NONCE: [u8; 32] = [7; 32];
+const EXPIRY: i64 = 2 * 86_400;
+
+struct Game {
+    svm: Svm,
+    p: Protocol,
+    creator: Pubkey,
+    vault: Pubkey,
+}
+
+fn game() -> Game {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let vault = p.salted_sol_vault(&mut svm, &creator, PIN, &SALT, None);
+    svm.update::<Vault>(&vault, |v| v.settlement_mode = SettlementMode::Claim as u8);
+    Game { svm, p, creator, vault }
+}
+
+impl Game {
+    /// A player with an attempt and a `commit_claim` to `pin` at the current slot.
+    fn committed_player(&mut self, pin: &[u8]) -> Pubkey {
+        let player = self.svm.wallet();
+        self.p.put_attempt(&mut self.svm, &self.vault, &player);
+        let seeds: &[&[u8]] = &[b"claim", self.vault.as_ref(), player.as_ref()];
+        let commitment = ClaimCommitment {
+            vault: self.vault,
+            player,
+            commitment: solana_sha256_hasher::hashv(&[pin, player.as_ref(), &NONCE]).to_bytes(),
+            slot: self.svm.clock().slot,
+            bump: bump(seeds),
+        };
+        self.svm.put(pda(seeds), &commitment, 8 + ClaimCommitment::LEN);
+        player
+    }
+
+    fn reveal_salt(&mut self) {
+        self.svm
+            .send_ix(ix(
+                accounts::RevealSalt {
+                    vault: self.vault,
+                    creator: self.creator,
+                    global_config: self.p.global_config,
+                },
+                instruction::RevealSalt {
+                    salt: SALT,
+                    pin: PIN.to_vec(),
+                    hint_salts: vec![],
+                },
+            ))
+            .unwrap();
+    }
+
+    fn claim_win(&mut self, player: &Pubkey, displaced: Option<&Pubkey>) -> std::result::Result<(), ProgramError> {
+        let vault_id = self.svm.get::<Vault>(&self.vault).vault_id;
+        self.svm.send_ix(ix(
+            accounts::ClaimWin {
+                vault: self.vault,
+                claim_commitment: pda(&[b"claim", self.vault.as_ref(), player.as_ref()]),
+                attempt_record: pda(&[b"attempt", self.vault.as_ref(), player.as_ref()]),
+                player_profile: pda(&[b"player", player.as_ref()]),
+                displaced_profile: displaced.map(|d| pda(&[b"player", d.as_ref()])),
+                player: *player,
+                system_program: system_program::ID,
+                gate_token_account: None,
+                global_config: self.p.global_config,
+                vault_index: vault_index_pda(vault_id),
+                global_stats: self.p.global_stats,
+            },
+            instruction::ClaimWin {
+                secret: PIN.to_vec(),
+                nonce: NONCE,
+                allowlist_proof: vec![],
+            },
+        ))
+    }
+
+    fn claim_prize(&mut self, winner: &Pubkey) -> std::result::Result<(), ProgramError> {
+        self.svm.send_ix(ix(
+            accounts::ClaimPrizeSol {
+                vault: self.vault,
+                winner: *winner,
+                global_config: self.p.global_config,
+                mint_stats: self.p.mint_stats(&Pubkey::default()),
+            },
+            instruction::ClaimPrizeSol {},
+        ))
+    }
+}
+
+#[test]
+fn salted_claims_wait_for_the_salt() {
+    let mut g = game();
+    let player = g.committed_player(PIN);
+    g.svm.warp(10, 60);
+    assert_eq!(g.claim_win(&player, None), Err(vault_err(VaultError::SaltNotRevealed)));
+
+    g.svm.warp(1_000, EXPIRY);
+    g.reveal_salt();
+    g.claim_win(&player, None).unwrap();
+    assert_eq!(g.svm.get::<Vault>(&g.vault).winner, Some(player));
+    assert_eq!(g.svm.get::<PlayerProfile>(&pda(&[b"player", player.as_ref()])).wins, 1);
+}
+
+#[test]
+fn older_commitment_displaces_during_the_window_and_then_gets_paid() {
+    let mut g = game();
+    let first = g.committed_player(PIN);
+    g.svm.warp(5, 5);
+    let second = g.committed_player(PIN);
+    g.svm.warp(1_000, EXPIRY);
+    g.reveal_salt();
+
+    g.claim_win(&second, None).unwrap();
+    assert_eq!(g.claim_prize(&second), Err(vault_err(VaultError::ClaimWindowOpen)));
+    g.claim_win(&first, Some(&second)).unwrap();
+    assert_eq!(g.svm.get::<Vault>(&g.vault).winner, Some(first));
+
+    g.svm.warp(1_000, 86_400 + 1);
+    let before = g.svm.lamports(&first);
+    g.claim_prize(&first).unwrap();
+    assert!(g.svm.lamports(&first) > before);
+}
+
+#[test]
+fn reveals_close_with_the_window() {
+    let mut g = game();
+    let player = g.committed_player(PIN);
+    g.svm.warp(1_000, EXPIRY);
+    g.reveal_salt();
+    g.svm.warp(1_000, 86_400 + 1);
+
+    assert_eq!(g.claim_win(&player, None), Err(vault_err(VaultError::VaultExpired)));
+}
+
+#[test]
+fn claims_must_be_committed_near_the_last_attempt() {
+    let mut g = game();
+    g.svm.update::<Vault>(&g.vault, |v| v.claim_window_slots = 5);
+    let player = g.svm.wallet();
+    g.p.put_attempt(&mut g.svm, &g.vault, &player);
+    g.svm.warp(6, 6);
+    let seeds: &[&[u8]] = &[b"claim", g.vault.as_ref(), player.as_ref()];
+    let commitment = ClaimCommitment {
+        vault: g.vault,
+        player,
+        commitment: solana_sha256_hasher::hashv(&[PIN, player.as_ref(), &NONCE]).to_bytes(),
+        slot: g.svm.clock().slot,
+        bump: bump(seeds),
+    };
+    g.svm.put(pda(seeds), &commitment, 8 + ClaimCommitment::LEN);
+    g.svm.warp(1_000, EXPIRY);
+    g.reveal_salt();
+
+    assert_eq!(g.claim_win(&player, None), Err(vault_err(VaultError::ClaimWindowElapsed)));
+}
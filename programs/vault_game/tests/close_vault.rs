@@ -0,0 +1,129 @@
+//! `close_vault` returns the rent of a paid-out vault and of its metadata to the creator.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use vault_game::{accounts, instruction, Vault, VaultError, VaultMetadata, VaultMetadataArgs};
+
+const PIN: &[u8] = b"4821";
+
+struct Game {
+    svm: Svm,
+    p: Protocol,
+    creator: Pubkey,
+    vault: Pubkey,
+}
+
+fn game() -> Game {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let vault = p.salted_sol_vault(&mut svm, &creator, PIN, &[9; 32], None);
+    Game { svm, p, creator, vault }
+}
+
+impl Game {
+    fn metadata_pda(&self) -> Pubkey {
+        pda(&[b"vault_meta", self.vault.as_ref()])
+    }
+
+    /// Give the vault metadata through `set_vault_metadata`, over the account its first call would
+    /// have created (creating it is a CPI).
+    fn add_metadata(&mut self) {
+        let seeds: &[&[u8]] = &[b"vault_meta", self.vault.as_ref()];
+        let metadata = VaultMetadata {
+            vault: self.vault,
+            title: String::new(),
+            description: String::new(),
+            category: String::new(),
+            uri: String::new(),
+            content_hash: [0; 32],
+            updated_ts: 0,
+            bump: bump(seeds),
+        };
+        self.svm.put(pda(seeds), &metadata, 8 + VaultMetadata::LEN);
+        self.svm
+            .send_ix(ix(
+                accounts::SetVaultMetadata {
+                    vault: self.vault,
+                    vault_metadata: self.metadata_pda(),
+                    creator: self.creator,
+                    global_config: self.p.global_config,
+                    system_program: system_program::ID,
+                },
+                instruction::SetVaultMetadata {
+                    args: VaultMetadataArgs {
+                        title: "Vault".to_string(),
+                        description: String::new(),
+                        category: "puzzle".to_string(),
+                        uri: String::new(),
+                        content_hash: [0; 32],
+                    },
+                },
+            ))
+            .unwrap();
+    }
+
+    /// The vault as `claim_prize` leaves it: paid out, nothing above rent.
+    fn pay_out(&mut self) {
+        self.svm.update::<Vault>(&self.vault, |v| v.paid_out = true);
+        let mut account = self.svm.account(&self.vault).unwrap().clone();
+        account.lamports = self.svm.rent(account.data.len());
+        self.svm.set_account(self.vault, account);
+    }
+
+    fn close(&mut self, with_metadata: bool) -> std::result::Result<(), ProgramError> {
+        let vault_id = self.svm.get::<Vault>(&self.vault).vault_id;
+        self.svm.send_ix(ix(
+            accounts::CloseVault {
+                vault: self.vault,
+                creator: self.creator,
+                mega_season: mega_season_pda(&Pubkey::default(), 0),
+                global_config: self.p.global_config,
+                fee_mint: None,
+                vault_fee_ata: None,
+                token_program: None,
+                vault_metadata: with_metadata.then(|| self.metadata_pda()),
+                vault_index: vault_index_pda(vault_id),
+            },
+            instruction::CloseVault {},
+        ))
+    }
+}
+
+#[test]
+fn vault_with_metadata_must_close_it_too() {
+    let mut g = game();
+    g.add_metadata();
+    assert!(g.svm.get::<Vault>(&g.vault).has_metadata);
+    g.pay_out();
+
+    assert_eq!(g.close(false), Err(vault_err(VaultError::MissingVaultMetadata)));
+
+    let before = g.svm.lamports(&g.creator);
+    let rent = g.svm.lamports(&g.vault) + g.svm.lamports(&g.metadata_pda());
+    g.close(true).unwrap();
+    assert!(g.svm.account(&g.vault).is_none());
+    assert!(g.svm.account(&g.metadata_pda()).is_none());
+    assert_eq!(g.svm.lamports(&g.creator), before + rent);
+}
+
+#[test]
+fn vault_without_metadata_closes_alone() {
+    let mut g = game();
+    g.pay_out();
+
+    let before = g.svm.lamports(&g.creator);
+    let rent = g.svm.lamports(&g.vault);
+    g.close(false).unwrap();
+    assert!(g.svm.account(&g.vault).is_none());
+    assert_eq!(g.svm.lamports(&g.creator), before + rent);
+}
+
+#[test]
+fn unpaid_vault_stays_open() {
+    let mut g = game();
+    assert_eq!(g.close(false), Err(vault_err(VaultError::VaultNotPaidOut)));
+}
@@ -0,0 +1,767 @@
+//! Off-chain test harness.
+//!
+//! Runs `vault_game::entry` against an in-memory account store, with the clock and rent syscalls
+//! served by `SyscallStubs` from a thread-local (so tests can run in parallel).
+//!
+//! Off-chain, Anchor's CPIs panic and its event logs are dropped (neither goes through the stubs).
+//! So only instruction paths without CPIs run here: accounts an instruction would create are
+//! written up front as fixtures (`Svm::put`), and events are not observable. Token-2022
+//! instructions can be sent at the top level, to drive the transfers a handler would make.
+//!
+//! Account buffers are laid out like the runtime's serialized input (original length before the
+//! key, data length before the data, realloc headroom after it) so `AccountInfo::resize` works.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use anchor_lang::solana_program::instruction::{BorrowedAccountMeta, BorrowedInstruction, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use solana_sysvar::program_stubs::{set_syscall_stubs, SyscallStubs};
+use vault_game::{
+    hints, AttemptRecord, GlobalConfig, GlobalState, GlobalStats, GuessFeedback, GuessRecord, LegacyVault,
+    MegaSeason, MegaSeasonStatus, MegaVault, MintStats, PlayerProfile, SecretScheme, SettlementMode, Vault,
+    VaultError, VaultIndexPage, VaultStatus,
+};
+
+pub const START_SLOT: u64 = 1_000;
+pub const START_TS: i64 = 1_700_000_000;
+pub const SOL: u64 = 1_000_000_000;
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+pub struct Svm {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Default for Svm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svm {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+        CLOCK.with(|c| {
+            *c.borrow_mut() = Clock {
+                slot: START_SLOT,
+                unix_timestamp: START_TS,
+                ..Clock::default()
+            }
+        });
+
+        let mut svm = Svm {
+            accounts: HashMap::new(),
+        };
+        for program in [
+            vault_game::ID,
+            system_program::ID,
+            solana_sdk_ids::ed25519_program::ID,
+            spl_token_2022::ID,
+        ] {
+            svm.accounts.insert(
+                program,
+                Account {
+                    lamports: 1,
+                    owner: solana_sdk_ids::bpf_loader_upgradeable::ID,
+                    executable: true,
+                    ..Account::default()
+                },
+            );
+        }
+        svm
+    }
+
+    pub fn clock(&self) -> Clock {
+        CLOCK.with(|c| c.borrow().clone())
+    }
+
+    /// Advance the clock by `slots` slots and `secs` seconds.
+    pub fn warp(&mut self, slots: u64, secs: i64) {
+        CLOCK.with(|c| {
+            let mut c = c.borrow_mut();
+            c.slot += slots;
+            c.unix_timestamp += secs;
+        });
+    }
+
+    pub fn rent(&self, len: usize) -> u64 {
+        Rent::default().minimum_balance(len)
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts.entry(*key).or_default().lamports += lamports;
+    }
+
+    /// A funded system wallet.
+    pub fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.airdrop(&key, 100 * SOL);
+        key
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |a| a.lamports)
+    }
+
+    pub fn get<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self.accounts.get(key).unwrap_or_else(|| panic!("missing account {key}"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("deserialize account")
+    }
+
+    /// Rewrite a program account in place (for state no instruction can reach).
+    pub fn update<T: AccountSerialize + AccountDeserialize>(&mut self, key: &Pubkey, f: impl FnOnce(&mut T)) {
+        let mut value = self.get::<T>(key);
+        f(&mut value);
+        let account = self.accounts.get_mut(key).unwrap();
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= account.data.len(), "account grew past its allocation");
+        account.data[..data.len()].copy_from_slice(&data);
+    }
+
+    /// Write a rent-exempt program account holding `value`, in `space` bytes.
+    pub fn put<T: AccountSerialize + Owner>(&mut self, key: Pubkey, value: &T, space: usize) {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        data.resize(space, 0);
+        let lamports = self.rent(space);
+        self.accounts.insert(
+            key,
+            Account {
+                lamports,
+                data,
+                owner: T::owner(),
+                executable: false,
+            },
+        );
+    }
+
+    pub fn send_ix(&mut self, ix: Instruction) -> std::result::Result<(), ProgramError> {
+        self.send(&[ix])
+    }
+
+    /// Run `ixs` as one transaction: all state changes are kept, or none are.
+    ///
+    /// Ed25519 program instructions are not executed (signature checks are out of scope here);
+    /// they only appear in the instructions sysvar.
+    pub fn send(&mut self, ixs: &[Instruction]) -> std::result::Result<(), ProgramError> {
+        let snapshot = self.accounts.clone();
+        let borrowed: Vec<BorrowedInstruction> = ixs
+            .iter()
+            .map(|ix| BorrowedInstruction {
+                program_id: &ix.program_id,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|m| BorrowedAccountMeta {
+                        pubkey: &m.pubkey,
+                        is_signer: m.is_signer,
+                        is_writable: m.is_writable,
+                    })
+                    .collect(),
+                data: &ix.data,
+            })
+            .collect();
+        let sysvar_data = solana_instructions_sysvar::construct_instructions_data(&borrowed);
+
+        for (i, ix) in ixs.iter().enumerate() {
+            if ix.program_id == solana_sdk_ids::ed25519_program::ID {
+                continue;
+            }
+            let mut sysvar = sysvar_data.clone();
+            solana_instructions_sysvar::store_current_index_checked(&mut sysvar, i as u16).unwrap();
+            self.accounts.insert(
+                solana_sdk_ids::sysvar::instructions::ID,
+                Account {
+                    lamports: 1,
+                    data: sysvar,
+                    owner: solana_sdk_ids::sysvar::ID,
+                    executable: false,
+                },
+            );
+            if let Err(err) = self.execute(ix) {
+                self.accounts = snapshot;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, ix: &Instruction) -> std::result::Result<(), ProgramError> {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        for meta in &ix.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+        }
+        let mut bufs: Vec<Buf> = keys
+            .iter()
+            .map(|k| Buf::new(k, &self.accounts.get(k).cloned().unwrap_or_default()))
+            .collect();
+        let pre_total: u128 = bufs.iter().map(|b| b.account().lamports as u128).sum();
+
+        {
+            let infos: Vec<AccountInfo> = bufs.iter_mut().map(Buf::info).collect();
+            let accounts: Vec<AccountInfo> = ix
+                .accounts
+                .iter()
+                .map(|m| {
+                    let mut info = infos[keys.iter().position(|k| *k == m.pubkey).unwrap()].clone();
+                    info.is_signer = m.is_signer;
+                    info.is_writable = m.is_writable;
+                    info
+                })
+                .collect();
+            dispatch(&ix.program_id, &accounts, &ix.data)?;
+        }
+
+        let post_total: u128 = bufs.iter().map(|b| b.account().lamports as u128).sum();
+        assert_eq!(pre_total, post_total, "instruction changed the total lamports");
+        for (key, buf) in keys.iter().zip(&bufs) {
+            let after = buf.account();
+            let writable = ix.accounts.iter().any(|m| m.pubkey == *key && m.is_writable);
+            if !writable {
+                let before = self.accounts.get(key).cloned().unwrap_or_default();
+                assert!(
+                    before.lamports == after.lamports && before.data == after.data && before.owner == after.owner,
+                    "read-only account {key} was modified"
+                );
+                continue;
+            }
+            if after.lamports == 0 && after.data.is_empty() {
+                self.accounts.remove(key);
+            } else {
+                self.accounts.insert(*key, after);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Offsets in a serialized account buffer.
+const ORIGINAL_LEN: usize = 4;
+const KEY: usize = 8;
+const OWNER: usize = 40;
+const LAMPORTS: usize = 72;
+const DATA_LEN: usize = 80;
+const DATA: usize = 88;
+
+struct Buf {
+    mem: Vec<u64>,
+    executable: bool,
+}
+
+impl Buf {
+    fn new(key: &Pubkey, account: &Account) -> Buf {
+        let len = account.data.len();
+        let mut mem = vec![0u64; (DATA + len + MAX_PERMITTED_DATA_INCREASE).div_ceil(8)];
+        let bytes = unsafe { std::slice::from_raw_parts_mut(mem.as_mut_ptr() as *mut u8, mem.len() * 8) };
+        bytes[ORIGINAL_LEN..KEY].copy_from_slice(&(len as u32).to_le_bytes());
+        bytes[KEY..OWNER].copy_from_slice(key.as_ref());
+        bytes[OWNER..LAMPORTS].copy_from_slice(account.owner.as_ref());
+        bytes[LAMPORTS..DATA_LEN].copy_from_slice(&account.lamports.to_le_bytes());
+        bytes[DATA_LEN..DATA].copy_from_slice(&(len as u64).to_le_bytes());
+        bytes[DATA..DATA + len].copy_from_slice(&account.data);
+        Buf {
+            mem,
+            executable: account.executable,
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.mem.as_ptr() as *const u8, self.mem.len() * 8) }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        let p = self.mem.as_mut_ptr() as *mut u8;
+        unsafe {
+            let len = *(p.add(DATA_LEN) as *const u64) as usize;
+            AccountInfo::new(
+                &*(p.add(KEY) as *const Pubkey),
+                false,
+                false,
+                &mut *(p.add(LAMPORTS) as *mut u64),
+                std::slice::from_raw_parts_mut(p.add(DATA), len),
+                &*(p.add(OWNER) as *const Pubkey),
+                self.executable,
+                0,
+            )
+        }
+    }
+
+    fn account(&self) -> Account {
+        let bytes = self.bytes();
+        let len = u64::from_le_bytes(bytes[DATA_LEN..DATA].try_into().unwrap()) as usize;
+        Account {
+            lamports: u64::from_le_bytes(bytes[LAMPORTS..DATA_LEN].try_into().unwrap()),
+            data: bytes[DATA..DATA + len].to_vec(),
+            owner: Pubkey::try_from(&bytes[OWNER..LAMPORTS]).unwrap(),
+            executable: self.executable,
+        }
+    }
+}
+
+fn dispatch<'a>(program_id: &Pubkey, accounts: &[AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+    if *program_id == spl_token_2022::ID {
+        return spl_token_2022::processor::Processor::process(program_id, accounts, data);
+    }
+    assert_eq!(*program_id, vault_game::ID, "only vault_game and Token-2022 instructions run off-chain");
+    // `entry` wants the slice to live as long as the infos; it does not outlive this call.
+    let accounts: &'a [AccountInfo<'a>] = unsafe { &*(accounts as *const [AccountInfo<'a>]) };
+    vault_game::entry(program_id, accounts, data)
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|c| c.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+}
+
+// -----------------
+// vault_game helpers
+// -----------------
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &vault_game::ID).0
+}
+
+pub fn bump(seeds: &[&[u8]]) -> u8 {
+    Pubkey::find_program_address(seeds, &vault_game::ID).1
+}
+
+pub fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: vault_game::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn vault_err(err: VaultError) -> ProgramError {
+    anchor_lang::error::Error::from(err).into()
+}
+
+pub fn vault_pda(vault_id: u64) -> Pubkey {
+    pda(&[b"vault", &vault_id.to_le_bytes()])
+}
+
+pub fn vault_index_pda(vault_id: u64) -> Pubkey {
+    pda(&[b"vault_index", &(vault_id / 256).to_le_bytes()])
+}
+
+pub fn mega_season_pda(fee_mint: &Pubkey, season_id: u64) -> Pubkey {
+    pda(&[b"mega_season", fee_mint.as_ref(), &season_id.to_le_bytes()])
+}
+
+/// Protocol singletons as the admin instructions would leave them: `GlobalState`, `GlobalConfig`
+/// (with a referee), `GlobalStats`, the SOL `MintStats`, the mega vault and SOL mega season 0.
+pub struct Protocol {
+    pub authority: Pubkey,
+    pub referee: Pubkey,
+    pub global_state: Pubkey,
+    pub global_config: Pubkey,
+    pub global_stats: Pubkey,
+    pub mega_vault: Pubkey,
+}
+
+impl Protocol {
+    pub fn setup(svm: &mut Svm) -> Protocol {
+        let p = Protocol {
+            authority: svm.wallet(),
+            referee: Pubkey::new_unique(),
+            global_state: pda(&[b"global"]),
+            global_config: pda(&[b"config"]),
+            global_stats: pda(&[b"stats"]),
+            mega_vault: pda(&[b"mega_vault"]),
+        };
+        svm.put(
+            p.global_state,
+            &GlobalState {
+                authority: p.authority,
+                skr_mint: Pubkey::new_unique(),
+                vault_count: 0,
+                bump: bump(&[b"global"]),
+            },
+            8 + GlobalState::LEN,
+        );
+        svm.put(
+            p.global_config,
+            &GlobalConfig {
+                referee: p.referee,
+                mega_payout_bps: None,
+                fee_curve_bounds: None,
+                default_max_fee_amount: None,
+                fee_split: None,
+                fee_split_bounds: None,
+                referral_bps: None,
+                paused: 0,
+                pending_authority: None,
+                bump: bump(&[b"config"]),
+            },
+            8 + GlobalConfig::LEN,
+        );
+        svm.put(
+            p.global_stats,
+            &GlobalStats {
+                vaults_created: 0,
+                active_vaults: 0,
+                total_attempts: 0,
+                rewards_added: 0,
+                rewards_claimed: 0,
+                rewards_reclaimed: 0,
+                bump: bump(&[b"stats"]),
+            },
+            8 + GlobalStats::LEN,
+        );
+        let sol = Pubkey::default();
+        svm.put(
+            p.mint_stats(&sol),
+            &MintStats {
+                mint: sol,
+                fees_collected: 0,
+                prizes_paid: 0,
+                reclaimed: 0,
+                mega_inflow: 0,
+                bump: bump(&[b"mint_stats", sol.as_ref()]),
+            },
+            8 + MintStats::LEN,
+        );
+        svm.put(
+            p.mega_vault,
+            &MegaVault {
+                bump: bump(&[b"mega_vault"]),
+            },
+            8 + MegaVault::LEN,
+        );
+        let season_seeds: &[&[u8]] = &[b"mega_season", sol.as_ref(), &0u64.to_le_bytes()];
+        svm.put(
+            pda(season_seeds),
+            &MegaSeason {
+                fee_mint: sol,
+                season_id: 0,
+                status: MegaSeasonStatus::Open as u8,
+                opening_balance: 0,
+                fee_inflows: 0,
+                reclaim_inflows: 0,
+                challenge_vault: None,
+                winner: None,
+                payout: 0,
+                closing_balance: 0,
+                opened_at: svm.clock().unix_timestamp,
+                closed_at: None,
+                bump: bump(season_seeds),
+            },
+            8 + MegaSeason::LEN,
+        );
+        p
+    }
+
+    pub fn mint_stats(&self, fee_mint: &Pubkey) -> Pubkey {
+        pda(&[b"mint_stats", fee_mint.as_ref()])
+    }
+
+    /// Register the next vault id and write `vault` (built for that id by `build`) plus its index page.
+    ///
+    /// SOL vaults hold `prize_amount` above rent.
+    pub fn put_vault(&self, svm: &mut Svm, build: impl FnOnce(u64) -> Vault) -> Pubkey {
+        let mut vault_id = 0;
+        svm.update::<GlobalState>(&self.global_state, |gs| {
+            vault_id = gs.vault_count;
+            gs.vault_count += 1;
+        });
+        let vault = build(vault_id);
+        let key = vault_pda(vault_id);
+        svm.put(key, &vault, 8 + Vault::LEN);
+        if vault.is_sol_fee {
+            svm.airdrop(&key, vault.prize_amount);
+        }
+
+        let index = vault_index_pda(vault_id);
+        if svm.account(&index).is_none() {
+            let page = vault_id / VaultIndexPage::ENTRIES;
+            let mut data = VaultIndexPage::DISCRIMINATOR.to_vec();
+            data.resize(8 + std::mem::size_of::<VaultIndexPage>(), 0);
+            data[8..16].copy_from_slice(&page.to_le_bytes());
+            data[24] = bump(&[b"vault_index", &page.to_le_bytes()]);
+            let lamports = svm.rent(data.len());
+            svm.set_account(
+                index,
+                Account {
+                    lamports,
+                    data,
+                    owner: vault_game::ID,
+                    executable: false,
+                },
+            );
+        }
+        key
+    }
+
+    /// A SOL vault as `migrate_vault` leaves a pre-upgrade one: unsalted, claim-settled,
+    /// `secret_hash = sha256(pin)`, open for a day.
+    pub fn legacy_sol_vault(&self, svm: &mut Svm, creator: &Pubkey, pin: &[u8], fee: u64, prize: u64) -> Pubkey {
+        let now = svm.clock().unix_timestamp;
+        self.put_vault(svm, |vault_id| {
+            Vault::from_legacy(LegacyVault {
+                creator: *creator,
+                status: VaultStatus::Active as u8,
+                created_at: now,
+                end_ts: now + 86_400,
+                secret_hash: solana_sha256_hasher::hash(pin).to_bytes(),
+                vault_id,
+                prize_amount: prize,
+                starting_fee_amount: fee,
+                current_fee_amount: fee,
+                attempt_count: 0,
+                is_sol_fee: true,
+                fee_mint: Pubkey::default(),
+                total_fees_collected: 0,
+                winner_fee_pool: 0,
+                winner: None,
+                settled_at: None,
+                paid_out: false,
+                bump: bump(&[b"vault", &vault_id.to_le_bytes()]),
+            })
+        })
+    }
+
+    /// The `PlayerProfile` + `AttemptRecord` a `make_guess_*` by `player` would have left.
+    pub fn put_attempt(&self, svm: &mut Svm, vault: &Pubkey, player: &Pubkey) {
+        let clock = svm.clock();
+        let profile_seeds: &[&[u8]] = &[b"player", player.as_ref()];
+        svm.put(
+            pda(profile_seeds),
+            &PlayerProfile {
+                authority: *player,
+                attempts: 1,
+                wins: 0,
+                vaults_created: 0,
+                score: 1,
+                last_seen_ts: clock.unix_timestamp,
+                bump: bump(profile_seeds),
+                referrer: None,
+                referral_earnings_sol: 0,
+            },
+            8 + PlayerProfile::LEN,
+        );
+        let attempt_seeds: &[&[u8]] = &[b"attempt", vault.as_ref(), player.as_ref()];
+        svm.put(
+            pda(attempt_seeds),
+            &AttemptRecord {
+                vault: *vault,
+                player: *player,
+                attempts: 1,
+                fees_paid: 0,
+                last_attempt_slot: clock.slot,
+                bump: bump(attempt_seeds),
+                last_attempt_ts: clock.unix_timestamp,
+            },
+            8 + AttemptRecord::LEN,
+        );
+    }
+}
+
+impl Protocol {
+    /// A salted, referee-settled SOL vault as `create_vault_sol` writes it, with verifiable hints
+    /// built from `hint_salts` when given. `pin` is ASCII text.
+    pub fn salted_sol_vault(
+        &self,
+        svm: &mut Svm,
+        creator: &Pubkey,
+        pin: &[u8],
+        salt: &[u8; 32],
+        hint_salts: Option<&[[u8; 32]]>,
+    ) -> Pubkey {
+        let key = self.legacy_sol_vault(svm, creator, pin, SOL / 100, SOL);
+        let digits: Vec<u8> = pin.iter().map(|c| c - b'0').collect();
+        svm.update::<Vault>(&key, |v| {
+            v.secret_hash = solana_sha256_hasher::hashv(&[salt, pin]).to_bytes();
+            v.secret_scheme = SecretScheme::SaltedSha256 as u8;
+            v.settlement_mode = SettlementMode::Referee as u8;
+            v.pin_len = pin.len() as u8;
+            v.hint_root = hint_salts.map(|salts| hints::root(&hints::build_tree(&digits, salts)));
+            v.rewards_untracked = false;
+        });
+        key
+    }
+
+    /// The `GuessRecord` `make_guess_*` writes for attempt `attempt_index`.
+    pub fn put_guess(&self, svm: &mut Svm, vault: &Pubkey, player: &Pubkey, attempt_index: u64, guess: [u8; 32]) -> Pubkey {
+        let seeds: &[&[u8]] = &[b"guess", vault.as_ref(), &attempt_index.to_le_bytes()];
+        let key = pda(seeds);
+        let record = GuessRecord {
+            vault: *vault,
+            player: *player,
+            attempt_index,
+            guess,
+            fee_paid: 0,
+            slot: svm.clock().slot,
+            feedback: GuessFeedback::default(),
+            bump: bump(seeds),
+        };
+        svm.put(key, &record, 8 + GuessRecord::LEN);
+        key
+    }
+}
+
+// -----------------
+// Token-2022 helpers
+// -----------------
+
+/// A Token-2022 mint with a transfer fee of `bps` basis points (capped at `max_fee`).
+pub struct FeeMint {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub decimals: u8,
+}
+
+impl FeeMint {
+    pub fn create(svm: &mut Svm, bps: u16, max_fee: u64) -> FeeMint {
+        use spl_token_2022::extension::ExtensionType;
+
+        let mint = Pubkey::new_unique();
+        let authority = svm.wallet();
+        let decimals = 6;
+        let len =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        svm.token_account_shell(mint, len);
+        svm.send(&[
+            spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint,
+                Some(&authority),
+                Some(&authority),
+                bps,
+                max_fee,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::ID, &mint, &authority, None, decimals).unwrap(),
+        ])
+        .unwrap();
+        FeeMint {
+            mint,
+            authority,
+            decimals,
+        }
+    }
+
+    /// A token account of this mint owned by `owner` (a wallet or a PDA).
+    pub fn account(&self, svm: &mut Svm, owner: &Pubkey) -> Pubkey {
+        use spl_token_2022::extension::ExtensionType;
+
+        let key = Pubkey::new_unique();
+        let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+            ExtensionType::TransferFeeAmount,
+        ])
+        .unwrap();
+        svm.token_account_shell(key, len);
+        svm.send_ix(spl_token_2022::instruction::initialize_account3(&spl_token_2022::ID, &key, &self.mint, owner).unwrap())
+            .unwrap();
+        key
+    }
+
+    pub fn mint_to(&self, svm: &mut Svm, account: &Pubkey, amount: u64) {
+        svm.send_ix(
+            spl_token_2022::instruction::mint_to_checked(
+                &spl_token_2022::ID,
+                &self.mint,
+                account,
+                &self.authority,
+                &[],
+                amount,
+                self.decimals,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    /// `transfer_checked` signed by `authority`; the destination is credited `amount` less the fee.
+    pub fn transfer(
+        &self,
+        svm: &mut Svm,
+        from: &Pubkey,
+        to: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), ProgramError> {
+        svm.send_ix(
+            spl_token_2022::instruction::transfer_checked(
+                &spl_token_2022::ID,
+                from,
+                &self.mint,
+                to,
+                authority,
+                &[],
+                amount,
+                self.decimals,
+            )
+            .unwrap(),
+        )
+    }
+}
+
+impl Svm {
+    /// A rent-exempt, zeroed account of `len` bytes owned by Token-2022, ready to initialize.
+    fn token_account_shell(&mut self, key: Pubkey, len: usize) {
+        let lamports = self.rent(len);
+        self.set_account(
+            key,
+            Account {
+                lamports,
+                data: vec![0; len],
+                owner: spl_token_2022::ID,
+                executable: false,
+            },
+        );
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        use spl_token_2022::extension::StateWithExtensions;
+
+        let account = self.accounts.get(key).unwrap_or_else(|| panic!("missing account {key}"));
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .expect("token account")
+            .base
+            .amount
+    }
+}
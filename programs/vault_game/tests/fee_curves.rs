@@ -0,0 +1,177 @@
+//! `fees::attempt_fee` / `fees::ladder_fee` quote exactly what `make_guess_*` charges.
+//!
+//! `make_guess_*` charges `Vault::current_fee` and then stores `Vault::next_fee`; these tests step
+//! a vault the same way and compare every attempt against the quote; the `handler_*` test runs
+//! `make_guess_spl` itself in the runtime (see `runtime`).
+
+mod runtime;
+
+use anchor_lang::prelude::*;
+use runtime::Runtime;
+use solana_signer::Signer;
+use vault_game::fees::{self, FeeCurve};
+use vault_game::{GuessMade, LegacyVault, Vault, VaultStatus};
+
+const CREATED_AT: i64 = 1_700_000_000;
+
+fn vault(curve: FeeCurve, starting_fee: u64, max_fee: u64) -> Vault {
+    let mut v = Vault::from_legacy(LegacyVault {
+        creator: Pubkey::new_unique(),
+        status: VaultStatus::Active as u8,
+        created_at: CREATED_AT,
+        end_ts: CREATED_AT + 30 * 86_400,
+        secret_hash: [0; 32],
+        vault_id: 0,
+        prize_amount: 0,
+        starting_fee_amount: starting_fee,
+        current_fee_amount: starting_fee,
+        attempt_count: 0,
+        is_sol_fee: false,
+        fee_mint: Pubkey::new_unique(),
+        total_fees_collected: 0,
+        winner_fee_pool: 0,
+        winner: None,
+        settled_at: None,
+        paid_out: false,
+        bump: 255,
+    });
+    v.fee_curve = curve;
+    v.max_fee_amount = max_fee;
+    v
+}
+
+/// Make `attempts` paid attempts, one every `gap_secs`, checking each charge against the quote.
+fn assert_quotes_match(curve: FeeCurve, starting_fee: u64, max_fee: u64, attempts: u64, gap_secs: i64) {
+    let mut v = vault(curve, starting_fee, max_fee);
+    for i in 0..attempts {
+        let now = CREATED_AT + i as i64 * gap_secs;
+        let elapsed = now - CREATED_AT;
+        assert_eq!(
+            v.current_fee(now),
+            fees::attempt_fee(&curve, starting_fee, max_fee, i, elapsed),
+            "{curve:?}: attempt {i}"
+        );
+        assert_eq!(
+            v.current_fee_amount,
+            fees::ladder_fee(&curve, starting_fee, max_fee, i),
+            "{curve:?}: ladder {i}"
+        );
+        v.current_fee_amount = v.next_fee();
+    }
+}
+
+#[test]
+fn geometric_quotes_match() {
+    assert_quotes_match(fees::DEFAULT_FEE_CURVE, 25_000, u64::MAX, 300, 60);
+    assert_quotes_match(FeeCurve::Geometric { ratio_bps: 10_001 }, 3, 1_000_000, 300, 60);
+}
+
+#[test]
+fn linear_and_flat_quotes_match() {
+    assert_quotes_match(FeeCurve::Linear { step: 2_500 }, 25_000, 1_000_000, 300, 60);
+    assert_quotes_match(FeeCurve::Flat, 25_000, 1_000_000, 300, 60);
+}
+
+#[test]
+fn capped_geometric_quotes_match() {
+    let curve = FeeCurve::CappedGeometric {
+        ratio_bps: 15_000,
+        cap: 400_000,
+    };
+    assert_quotes_match(curve, 25_000, u64::MAX, 300, 60);
+    // The vault cap below the curve's own cap wins.
+    assert_quotes_match(curve, 25_000, 100_000, 300, 60);
+}
+
+#[test]
+fn time_decay_quotes_match() {
+    let curve = FeeCurve::TimeDecay {
+        ratio_bps: 11_000,
+        decay_bps: 500,
+        period_secs: 3_600,
+        floor_bps: 2_000,
+    };
+    // Sparse attempts sink to the floor; dense ones mostly climb the ladder.
+    assert_quotes_match(curve, 25_000, 10_000_000, 300, 1_800);
+    assert_quotes_match(curve, 25_000, 10_000_000, 300, 7);
+}
+
+/// A busy token vault: the ladder climbs to `max_fee_amount` and stays there, with the books
+/// `make_guess_spl` keeps (`Vault::record_paid_attempt`) matching the quoted fees.
+#[test]
+fn hundreds_of_token_attempts_saturate_at_max_fee() {
+    let starting_fee = fees::starting_fee(1_000_000, 4).unwrap();
+    let max_fee = 1_000_000_000_000;
+    let mut v = vault(fees::DEFAULT_FEE_CURVE, starting_fee, max_fee);
+
+    let mut charged = 0u64;
+    let mut pool = 0u64;
+    for i in 0..600u64 {
+        let now = CREATED_AT + i as i64;
+        let fee = v.current_fee(now);
+        assert_eq!(fee, fees::attempt_fee(&v.fee_curve, starting_fee, max_fee, i, now - CREATED_AT));
+        assert!(fee <= max_fee);
+        let winner_cut = v.fee_split.split(fee).winner;
+        v.record_paid_attempt(fee, winner_cut).unwrap();
+        charged += fee;
+        pool += winner_cut;
+    }
+
+    assert_eq!(v.current_fee_amount, max_fee);
+    assert_eq!(v.next_fee(), max_fee);
+    assert_eq!(v.attempt_count, 600);
+    assert_eq!(v.total_fees_collected, charged);
+    assert_eq!(v.winner_fee_pool, pool);
+}
+
+/// Without a cap the ladder pins at `u64::MAX` instead of overflowing.
+#[test]
+fn uncapped_ladder_pins_at_u64_max() {
+    let mut v = vault(fees::DEFAULT_FEE_CURVE, 25_000_000, u64::MAX);
+    for _ in 0..500 {
+        v.current_fee_amount = v.next_fee();
+    }
+    assert_eq!(v.current_fee_amount, u64::MAX);
+    assert_eq!(v.current_fee(CREATED_AT), u64::MAX);
+}
+
+/// `make_guess_spl` hundreds of times on a capped vault: every charge matches the quote, the fee
+/// settles at `max_fee_amount`, and the vault keeps accepting guesses there.
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_keeps_accepting_guesses_at_max_fee() {
+    let max_fee = 1_000_000_000_000;
+    let mut rt = Runtime::new();
+    let mint = rt.create_fee_mint(0, 0);
+    rt.register_fee_mint(&mint, 0, 0);
+    let creator = rt.wallet();
+    let player = rt.wallet();
+    let player_ata = rt.fund(&player.pubkey(), &mint, 400 * max_fee);
+    let mut args = runtime::vault_args(rt.now(), Some(mint), 0, 1_000_000);
+    args.max_fee_amount = Some(max_fee);
+    let (key, _) = rt.create_vault(&creator, args).unwrap();
+    let created = rt.get::<Vault>(&key);
+
+    let mut charged = 0u64;
+    for i in 0..300u64 {
+        let guess = rt.make_guess_spl(&key, &player).unwrap().event::<GuessMade>();
+        let elapsed = rt.now() - created.created_at;
+        assert_eq!(guess.attempt_index, i);
+        assert_eq!(
+            guess.fee,
+            fees::attempt_fee(&created.fee_curve, created.starting_fee_amount, max_fee, i, elapsed)
+        );
+        charged += guess.fee;
+    }
+    let v = rt.get::<Vault>(&key);
+    assert_eq!(v.current_fee_amount, max_fee);
+    assert_eq!(v.total_fees_collected, charged);
+
+    let guess = rt.make_guess_spl(&key, &player).unwrap().event::<GuessMade>();
+    assert_eq!(guess.fee, max_fee);
+    let v = rt.get::<Vault>(&key);
+    assert_eq!(v.attempt_count, 301);
+    assert_eq!(v.current_fee_amount, max_fee);
+    assert_eq!(rt.token_amount(&player_ata), 400 * max_fee - charged - max_fee);
+    assert_eq!(rt.token_amount(&runtime::ata(&key, &mint)), v.winner_fee_pool);
+}
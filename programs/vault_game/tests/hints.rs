@@ -0,0 +1,212 @@
+//! Verifiable hints: what `submit_hint` may reveal, and `reveal_salt` binding the hint root to the PIN.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use common::*;
+use vault_game::{
+    accounts, hints, instruction, DigitProof, GlobalConfig, GuessRecord, Vault, VaultError, PAUSE_GUESS, PAUSE_WITHDRAW,
+};
+
+const PIN: &[u8] = b"4821";
+const DIGITS: [u8; 4] = [4, 8, 2, 1];
+const SALT: [u8; 32] = [9; 32];
+const NONCE: [u8; 32] = [3; 32];
+
+fn hint_salts() -> Vec<[u8; 32]> {
+    (1..=4).map(|i| [i; 32]).collect()
+}
+
+struct Game {
+    svm: Svm,
+    p: Protocol,
+    creator: Pubkey,
+    player: Pubkey,
+    vault: Pubkey,
+    attempts: u64,
+}
+
+fn game() -> Game {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let player = svm.wallet();
+    let vault = p.salted_sol_vault(&mut svm, &creator, PIN, &SALT, Some(&hint_salts()));
+    Game {
+        svm,
+        p,
+        creator,
+        player,
+        vault,
+        attempts: 0,
+    }
+}
+
+impl Game {
+    /// Record a guess of `digits` by the player; returns its attempt index.
+    fn guess(&mut self, digits: &[u8]) -> u64 {
+        let guess = solana_sha256_hasher::hashv(&[digits, self.player.as_ref(), &NONCE]).to_bytes();
+        let attempt_index = self.attempts;
+        self.p
+            .put_guess(&mut self.svm, &self.vault, &self.player, attempt_index, guess);
+        self.attempts += 1;
+        attempt_index
+    }
+
+    fn submit_hint(&mut self, attempt_index: u64, digits: &[u8], positions: &[usize]) -> std::result::Result<(), ProgramError> {
+        let salts = hint_salts();
+        let levels = hints::build_tree(&DIGITS, &salts);
+        let proofs = positions
+            .iter()
+            .map(|&pos| DigitProof {
+                position: pos as u8,
+                digit: DIGITS[pos],
+                salt: salts[pos],
+                siblings: hints::proof(&levels, pos),
+            })
+            .collect();
+        self.svm.send_ix(ix(
+            accounts::SubmitHint {
+                vault: self.vault,
+                global_config: self.p.global_config,
+                guess_record: pda(&[b"guess", self.vault.as_ref(), &attempt_index.to_le_bytes()]),
+                authority: self.creator,
+            },
+            instruction::SubmitHint {
+                attempt_index,
+                guess_digits: digits.to_vec(),
+                guess_nonce: NONCE,
+                proofs,
+            },
+        ))
+    }
+
+    fn reveal_salt(&mut self, hint_salts: Vec<[u8; 32]>) -> std::result::Result<(), ProgramError> {
+        self.svm.send_ix(ix(
+            accounts::RevealSalt {
+                vault: self.vault,
+                creator: self.creator,
+                global_config: self.p.global_config,
+            },
+            instruction::RevealSalt {
+                salt: SALT,
+                pin: PIN.to_vec(),
+                hint_salts,
+            },
+        ))
+    }
+
+    /// `(exact, partial)` recorded on the attempt.
+    fn hint(&self, attempt_index: u64) -> Option<(u8, u8)> {
+        let record = pda(&[b"guess", self.vault.as_ref(), &attempt_index.to_le_bytes()]);
+        let hint = self.svm.get::<GuessRecord>(&record).feedback.hint;
+        hint.map(|h| (h.exact, h.partial))
+    }
+}
+
+#[test]
+fn hint_counts_exact_and_partial_from_revealed_positions() {
+    let mut g = game();
+    let guess = [4, 1, 2, 8];
+    let attempt = g.guess(&guess);
+    g.submit_hint(attempt, &guess, &[0, 1, 3]).unwrap();
+
+    assert_eq!(g.hint(attempt), Some((1, 2)));
+    assert_eq!(g.svm.get::<Vault>(&g.vault).hint_positions_opened, 0b1011);
+}
+
+#[test]
+fn leaf_digit_must_appear_in_the_guess() {
+    let mut g = game();
+    let guess = [4, 2, 7, 7];
+    let attempt = g.guess(&guess);
+    // Position 1 holds an 8, which the player did not guess.
+    assert_eq!(
+        g.submit_hint(attempt, &guess, &[0, 1]),
+        Err(vault_err(VaultError::HintDigitNotGuessed))
+    );
+    g.submit_hint(attempt, &guess, &[0, 2]).unwrap();
+    assert_eq!(g.hint(attempt), Some((1, 0)));
+}
+
+#[test]
+fn hints_never_open_every_position() {
+    let mut g = game();
+    let first = [4, 1, 2, 8];
+    let attempt = g.guess(&first);
+    g.submit_hint(attempt, &first, &[0, 1, 3]).unwrap();
+
+    // Opening the last closed position would publish the whole PIN.
+    let second = [5, 5, 2, 5];
+    let attempt = g.guess(&second);
+    assert_eq!(
+        g.submit_hint(attempt, &second, &[2]),
+        Err(vault_err(VaultError::HintLimitReached))
+    );
+
+    // Already-public positions can be reused.
+    let third = [4, 5, 5, 5];
+    let attempt = g.guess(&third);
+    g.submit_hint(attempt, &third, &[0]).unwrap();
+    assert_eq!(g.hint(attempt), Some((1, 0)));
+}
+
+#[test]
+fn reveal_salt_checks_hint_salts_against_the_root() {
+    let mut g = game();
+    g.svm.warp(1_000, 2 * 86_400);
+
+    let mut wrong = hint_salts();
+    wrong[3] = [0; 32];
+    assert_eq!(g.reveal_salt(wrong), Err(vault_err(VaultError::BadHintRoot)));
+    assert_eq!(g.reveal_salt(vec![]), Err(vault_err(VaultError::BadHintRoot)));
+
+    g.reveal_salt(hint_salts()).unwrap();
+    assert!(g.svm.get::<Vault>(&g.vault).salt_revealed);
+}
+
+#[test]
+fn reveal_salt_rejects_hints_built_over_another_pin() {
+    let mut g = game();
+    let other = hints::root(&hints::build_tree(&[4, 8, 2, 2], &hint_salts()));
+    g.svm.update::<Vault>(&g.vault, |v| v.hint_root = Some(other));
+    g.svm.warp(1_000, 2 * 86_400);
+
+    assert_eq!(g.reveal_salt(hint_salts()), Err(vault_err(VaultError::BadHintRoot)));
+}
+
+#[test]
+fn reveal_salt_without_hints_ignores_hint_salts() {
+    let mut g = game();
+    g.svm.update::<Vault>(&g.vault, |v| v.hint_root = None);
+    g.svm.warp(1_000, 2 * 86_400);
+
+    g.reveal_salt(vec![]).unwrap();
+    assert!(g.svm.get::<Vault>(&g.vault).salt_revealed);
+}
+
+#[test]
+fn submit_hint_respects_the_guess_pause() {
+    let mut g = game();
+    let guess = [4, 1, 2, 8];
+    let attempt = g.guess(&guess);
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = PAUSE_GUESS);
+    assert_eq!(
+        g.submit_hint(attempt, &guess, &[0]),
+        Err(vault_err(VaultError::EmergencyPaused))
+    );
+
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = 0);
+    g.submit_hint(attempt, &guess, &[0]).unwrap();
+}
+
+#[test]
+fn reveal_salt_respects_the_withdraw_pause() {
+    let mut g = game();
+    g.svm.warp(1_000, 2 * 86_400);
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = PAUSE_WITHDRAW);
+    assert_eq!(g.reveal_salt(hint_salts()), Err(vault_err(VaultError::EmergencyPaused)));
+
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = 0);
+    g.reveal_salt(hint_salts()).unwrap();
+}
@@ -0,0 +1,70 @@
+//! Mega seasons: only the season PDAs are accepted, and a won season cannot be held forever.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+use vault_game::{accounts, instruction, Vault, VaultError};
+
+const PIN: &[u8] = b"4821";
+const DAY: i64 = 86_400;
+
+fn reclaim(svm: &mut Svm, p: &Protocol, vault: &Pubkey, creator: &Pubkey, season: Pubkey) -> std::result::Result<(), ProgramError> {
+    let vault_id = svm.get::<Vault>(vault).vault_id;
+    svm.send_ix(ix(
+        accounts::ReclaimPrizeSol {
+            vault: *vault,
+            mega_vault: p.mega_vault,
+            creator: *creator,
+            mega_season: season,
+            global_config: p.global_config,
+            vault_index: vault_index_pda(vault_id),
+            global_stats: p.global_stats,
+            mint_stats: p.mint_stats(&Pubkey::default()),
+        },
+        instruction::ReclaimPrizeSol {},
+    ))
+}
+
+#[test]
+fn copied_season_account_is_rejected() {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let vault = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    svm.warp(1_000, 2 * DAY);
+
+    // Same data and owner as the open SOL season, at an address that is not its PDA.
+    let season = mega_season_pda(&Pubkey::default(), 0);
+    let copy = Pubkey::new_unique();
+    let account = svm.account(&season).unwrap().clone();
+    svm.set_account(copy, account);
+
+    assert_eq!(
+        reclaim(&mut svm, &p, &vault, &creator, copy),
+        Err(anchor_lang::error::Error::from(ErrorCode::ConstraintSeeds).into())
+    );
+    reclaim(&mut svm, &p, &vault, &creator, season).unwrap();
+}
+
+#[test]
+fn won_season_rolls_over_once_the_winner_times_out() {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let key = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    let mut vault = svm.get::<Vault>(&key);
+    let end_ts = vault.end_ts;
+
+    assert_eq!(vault.check_mega_rollover(end_ts), Err(VaultError::VaultNotExpired.into()));
+    vault.check_mega_rollover(end_ts + 1).unwrap();
+
+    vault.winner = Some(Pubkey::new_unique());
+    assert_eq!(vault.check_mega_rollover(end_ts + 1), Err(VaultError::MegaClaimPending.into()));
+    assert_eq!(
+        vault.check_mega_rollover(end_ts + 30 * DAY),
+        Err(VaultError::MegaClaimPending.into())
+    );
+    vault.check_mega_rollover(end_ts + 30 * DAY + 1).unwrap();
+}
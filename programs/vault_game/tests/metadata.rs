@@ -0,0 +1,212 @@
+//! Vault metadata: created with the vault from `CreateVaultArgs.metadata`, and edited by the
+//! creator with `set_vault_metadata` until the first attempt.
+
+mod common;
+mod runtime;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use runtime::Runtime;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use vault_game::{
+    accounts, instruction, GlobalConfig, Vault, VaultError, VaultMetadata, VaultMetadataArgs, VaultMetadataUpdated,
+    PAUSE_CREATE,
+};
+
+const PIN: &[u8] = b"4821";
+
+struct Game {
+    svm: Svm,
+    p: Protocol,
+    creator: Pubkey,
+    vault: Pubkey,
+}
+
+fn game() -> Game {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let vault = p.salted_sol_vault(&mut svm, &creator, PIN, &[9; 32], None);
+    Game { svm, p, creator, vault }
+}
+
+impl Game {
+    fn metadata_pda(&self) -> Pubkey {
+        pda(&[b"vault_meta", self.vault.as_ref()])
+    }
+
+    /// The metadata account a first `set_vault_metadata` would have created (creating it is a CPI).
+    fn put_metadata(&mut self) {
+        let seeds: &[&[u8]] = &[b"vault_meta", self.vault.as_ref()];
+        let metadata = VaultMetadata {
+            vault: self.vault,
+            title: "Old".to_string(),
+            description: String::new(),
+            category: String::new(),
+            uri: String::new(),
+            content_hash: [0; 32],
+            updated_ts: 0,
+            bump: bump(seeds),
+        };
+        self.svm.put(pda(seeds), &metadata, 8 + VaultMetadata::LEN);
+    }
+
+    fn set_metadata(&mut self, title: &str) -> std::result::Result<(), ProgramError> {
+        let creator = self.creator;
+        self.set_metadata_as(&creator, title)
+    }
+
+    fn set_metadata_as(&mut self, signer: &Pubkey, title: &str) -> std::result::Result<(), ProgramError> {
+        self.svm.send_ix(ix(
+            accounts::SetVaultMetadata {
+                vault: self.vault,
+                vault_metadata: self.metadata_pda(),
+                creator: *signer,
+                global_config: self.p.global_config,
+                system_program: system_program::ID,
+            },
+            instruction::SetVaultMetadata {
+                args: VaultMetadataArgs {
+                    title: title.to_string(),
+                    description: "Guess the PIN".to_string(),
+                    category: "puzzle".to_string(),
+                    uri: "https://example.com/vault.json".to_string(),
+                    content_hash: [1; 32],
+                },
+            },
+        ))
+    }
+}
+
+#[test]
+fn set_vault_metadata_respects_the_create_pause() {
+    let mut g = game();
+    g.put_metadata();
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = PAUSE_CREATE);
+    assert_eq!(g.set_metadata("New"), Err(vault_err(VaultError::EmergencyPaused)));
+
+    g.svm.update::<GlobalConfig>(&g.p.global_config, |gc| gc.paused = 0);
+    g.set_metadata("New").unwrap();
+    assert_eq!(g.svm.get::<VaultMetadata>(&g.metadata_pda()).title, "New");
+}
+
+#[test]
+fn set_vault_metadata_marks_the_vault() {
+    let mut g = game();
+    g.put_metadata();
+    assert!(!g.svm.get::<Vault>(&g.vault).has_metadata);
+    g.set_metadata("New").unwrap();
+    assert!(g.svm.get::<Vault>(&g.vault).has_metadata);
+}
+
+#[test]
+fn only_the_creator_edits_and_only_before_the_first_attempt() {
+    let mut g = game();
+    g.put_metadata();
+    let stranger = g.svm.wallet();
+    assert_eq!(g.set_metadata_as(&stranger, "Mine"), Err(vault_err(VaultError::NotCreator)));
+
+    g.set_metadata("New").unwrap();
+    g.svm.update::<Vault>(&g.vault, |v| v.attempt_count = 1);
+    assert_eq!(g.set_metadata("Newer"), Err(vault_err(VaultError::MetadataLocked)));
+    assert_eq!(g.svm.get::<VaultMetadata>(&g.metadata_pda()).title, "New");
+}
+
+fn metadata_args(title: &str) -> VaultMetadataArgs {
+    VaultMetadataArgs {
+        title: title.to_string(),
+        description: "Guess the PIN".to_string(),
+        category: "puzzle".to_string(),
+        uri: "https://example.com/vault.json".to_string(),
+        content_hash: [1; 32],
+    }
+}
+
+/// `create_vault_sol` with `metadata`, passing the metadata account only if `with_account`.
+fn create_sol(
+    rt: &mut Runtime,
+    creator: &Keypair,
+    metadata: Option<VaultMetadataArgs>,
+    with_account: bool,
+) -> std::result::Result<runtime::Logs, solana_transaction_error::TransactionError> {
+    let vault = runtime::vault_pda(rt.get::<vault_game::GlobalState>(&runtime::pda(&[b"global"])).vault_count);
+    let mut args = runtime::vault_args(rt.now(), None, SOL, 1_000_000);
+    args.metadata = metadata;
+    rt.send(
+        &[runtime::ix(
+            accounts::CreateVaultSol {
+                global_state: runtime::pda(&[b"global"]),
+                vault,
+                player_profile: runtime::player_pda(&creator.pubkey()),
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+                global_config: runtime::pda(&[b"config"]),
+                vault_index: runtime::vault_index_pda(0),
+                creator_index: runtime::pda(&[b"creator_vaults", creator.pubkey().as_ref(), &0u64.to_le_bytes()]),
+                global_stats: runtime::pda(&[b"stats"]),
+                vault_metadata: with_account.then(|| runtime::pda(&[b"vault_meta", vault.as_ref()])),
+            },
+            instruction::CreateVaultSol { args },
+        )],
+        &[creator],
+    )
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_create_vault_writes_its_metadata() {
+    let mut rt = Runtime::new();
+    let creator = rt.wallet();
+    let logs = create_sol(&mut rt, &creator, Some(metadata_args("Vault")), true).unwrap();
+
+    let vault = runtime::vault_pda(0);
+    let metadata_pda = runtime::pda(&[b"vault_meta", vault.as_ref()]);
+    assert!(rt.get::<Vault>(&vault).has_metadata);
+    let metadata = rt.get::<VaultMetadata>(&metadata_pda);
+    assert_eq!((metadata.vault, metadata.title.as_str()), (vault, "Vault"));
+    assert_eq!(metadata.uri, "https://example.com/vault.json");
+    assert_eq!(logs.event::<VaultMetadataUpdated>().title, "Vault");
+
+    // Still editable before the first attempt.
+    rt.send(
+        &[runtime::ix(
+            accounts::SetVaultMetadata {
+                vault,
+                vault_metadata: metadata_pda,
+                creator: creator.pubkey(),
+                global_config: runtime::pda(&[b"config"]),
+                system_program: system_program::ID,
+            },
+            instruction::SetVaultMetadata {
+                args: metadata_args("Renamed"),
+            },
+        )],
+        &[&creator],
+    )
+    .unwrap();
+    assert_eq!(rt.get::<VaultMetadata>(&metadata_pda).title, "Renamed");
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_metadata_and_its_account_come_together() {
+    let mut rt = Runtime::new();
+    let creator = rt.wallet();
+    assert_eq!(
+        create_sol(&mut rt, &creator, Some(metadata_args("Vault")), false).unwrap_err(),
+        runtime::vault_err(0, VaultError::MissingVaultMetadata)
+    );
+    assert_eq!(
+        create_sol(&mut rt, &creator, None, true).unwrap_err(),
+        runtime::vault_err(0, VaultError::UnexpectedVaultMetadata)
+    );
+    assert_eq!(
+        create_sol(&mut rt, &creator, Some(metadata_args(" ")), true).unwrap_err(),
+        runtime::vault_err(0, VaultError::BadMetadataLength)
+    );
+
+    create_sol(&mut rt, &creator, None, false).unwrap();
+    assert!(!rt.get::<Vault>(&runtime::vault_pda(0)).has_metadata);
+}
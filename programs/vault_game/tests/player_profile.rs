@@ -0,0 +1,124 @@
+//! `migrate_player_profile` grows profiles created before the referral fields were appended.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use vault_game::{accounts, instruction, ClaimCommitment, PlayerProfile, Vault, VaultError};
+
+const PIN: &[u8] = b"4821";
+const NONCE: [u8; 32] = [7; 32];
+
+fn profile_pda(player: &Pubkey) -> Pubkey {
+    pda(&[b"player", player.as_ref()])
+}
+
+/// Cut `player`'s profile back to its pre-upgrade size. It stays funded for the full size, so
+/// migrating needs no rent top-up (a CPI, which does not run off-chain).
+fn make_legacy(svm: &mut Svm, player: &Pubkey) {
+    let key = profile_pda(player);
+    let mut account = svm.account(&key).unwrap().clone();
+    account.data.truncate(8 + PlayerProfile::LEGACY_LEN);
+    svm.set_account(key, account);
+}
+
+fn migrate(svm: &mut Svm, player: &Pubkey) -> std::result::Result<(), ProgramError> {
+    let payer = svm.wallet();
+    svm.send_ix(ix(
+        accounts::MigratePlayerProfile {
+            player_profile: profile_pda(player),
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePlayerProfile {},
+    ))
+}
+
+fn claim_win(svm: &mut Svm, p: &Protocol, vault: &Pubkey, player: &Pubkey) -> std::result::Result<(), ProgramError> {
+    let vault_id = svm.get::<Vault>(vault).vault_id;
+    svm.send_ix(ix(
+        accounts::ClaimWin {
+            vault: *vault,
+            claim_commitment: pda(&[b"claim", vault.as_ref(), player.as_ref()]),
+            attempt_record: pda(&[b"attempt", vault.as_ref(), player.as_ref()]),
+            player_profile: profile_pda(player),
+            displaced_profile: None,
+            player: *player,
+            system_program: system_program::ID,
+            gate_token_account: None,
+            global_config: p.global_config,
+            vault_index: vault_index_pda(vault_id),
+            global_stats: p.global_stats,
+        },
+        instruction::ClaimWin {
+            secret: PIN.to_vec(),
+            nonce: NONCE,
+            allowlist_proof: vec![],
+        },
+    ))
+}
+
+#[test]
+fn migrated_profile_keeps_its_stats_and_starts_without_referrer() {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let player = svm.wallet();
+    let vault = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    p.put_attempt(&mut svm, &vault, &player);
+    svm.update::<PlayerProfile>(&profile_pda(&player), |pp| {
+        pp.score = 42;
+        pp.referral_earnings_sol = u64::MAX;
+    });
+    make_legacy(&mut svm, &player);
+
+    migrate(&mut svm, &player).unwrap();
+
+    let pp = svm.get::<PlayerProfile>(&profile_pda(&player));
+    assert_eq!(svm.account(&profile_pda(&player)).unwrap().data.len(), 8 + PlayerProfile::LEN);
+    assert_eq!((pp.authority, pp.attempts, pp.score), (player, 1, 42));
+    assert_eq!(pp.referrer, None);
+    assert_eq!(pp.referral_earnings_sol, 0);
+}
+
+#[test]
+fn only_pre_upgrade_profiles_migrate() {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let player = svm.wallet();
+    let vault = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    p.put_attempt(&mut svm, &vault, &player);
+
+    assert_eq!(migrate(&mut svm, &player), Err(vault_err(VaultError::NotLegacyProfile)));
+    make_legacy(&mut svm, &player);
+    migrate(&mut svm, &player).unwrap();
+    assert_eq!(migrate(&mut svm, &player), Err(vault_err(VaultError::NotLegacyProfile)));
+}
+
+#[test]
+fn legacy_profile_can_claim_once_migrated() {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let player = svm.wallet();
+    let vault = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    p.put_attempt(&mut svm, &vault, &player);
+    let seeds: &[&[u8]] = &[b"claim", vault.as_ref(), player.as_ref()];
+    let commitment = ClaimCommitment {
+        vault,
+        player,
+        commitment: solana_sha256_hasher::hashv(&[PIN, player.as_ref(), &NONCE]).to_bytes(),
+        slot: svm.clock().slot,
+        bump: bump(seeds),
+    };
+    svm.put(pda(seeds), &commitment, 8 + ClaimCommitment::LEN);
+    svm.warp(10, 5);
+    make_legacy(&mut svm, &player);
+
+    assert!(claim_win(&mut svm, &p, &vault, &player).is_err());
+    migrate(&mut svm, &player).unwrap();
+    claim_win(&mut svm, &p, &vault, &player).unwrap();
+    assert_eq!(svm.get::<Vault>(&vault).winner, Some(player));
+}
@@ -0,0 +1,71 @@
+//! SPL referral cuts are booked per referrer and fee mint in `ReferralEarnings`.
+
+mod runtime;
+
+use anchor_lang::prelude::*;
+use runtime::Runtime;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use vault_game::{instruction, GuessMade, ReferralEarnings};
+
+fn earnings_pda(referrer: &Pubkey, mint: &Pubkey) -> Pubkey {
+    runtime::pda(&[b"referral", referrer.as_ref(), mint.as_ref()])
+}
+
+/// A fee mint with an open vault, and `player`'s tokens to guess with.
+fn vault_in_new_mint(rt: &mut Runtime, player: &Keypair) -> (Pubkey, Pubkey) {
+    let mint = rt.create_fee_mint(0, 0);
+    rt.register_fee_mint(&mint, 0, 0);
+    let creator = rt.wallet();
+    rt.fund(&creator.pubkey(), &mint, 0);
+    rt.fund(&player.pubkey(), &mint, 1_000 * runtime::SOL);
+    let args = runtime::vault_args(rt.now(), Some(mint), 0, 1_000_000);
+    let (vault, _) = rt.create_vault(&creator, args).unwrap();
+    (mint, vault)
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn spl_referral_cuts_are_tracked_per_mint() {
+    let mut rt = Runtime::new();
+    rt.admin(runtime::ix(rt.config_accounts(), instruction::SetReferralBps { bps: 1_000 }));
+    let referrer = rt.wallet();
+    let player = rt.wallet();
+    rt.touch_player(&referrer);
+    rt.bind_referrer(&player, &referrer.pubkey());
+
+    let (first, first_vault) = vault_in_new_mint(&mut rt, &player);
+    let (second, second_vault) = vault_in_new_mint(&mut rt, &player);
+    rt.ata(&referrer.pubkey(), &first);
+    rt.ata(&referrer.pubkey(), &second);
+
+    let mut cuts = [0u64; 2];
+    for _ in 0..3 {
+        let guess = rt.make_guess_spl(&first_vault, &player).unwrap().event::<GuessMade>();
+        assert_eq!(guess.referrer, Some(referrer.pubkey()));
+        cuts[0] += guess.referral_cut;
+    }
+    let guess = rt.make_guess_spl(&second_vault, &player).unwrap().event::<GuessMade>();
+    cuts[1] += guess.referral_cut;
+    assert!(cuts.iter().all(|c| *c > 0));
+
+    for (mint, cut) in [(first, cuts[0]), (second, cuts[1])] {
+        let earnings = rt.get::<ReferralEarnings>(&earnings_pda(&referrer.pubkey(), &mint));
+        assert_eq!((earnings.referrer, earnings.mint), (referrer.pubkey(), mint));
+        assert_eq!(earnings.earned, cut);
+        assert_eq!(rt.token_amount(&runtime::ata(&referrer.pubkey(), &mint)), cut);
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn guesses_without_a_referrer_book_nothing() {
+    let mut rt = Runtime::new();
+    rt.admin(runtime::ix(rt.config_accounts(), instruction::SetReferralBps { bps: 1_000 }));
+    let player = rt.wallet();
+    let (mint, vault) = vault_in_new_mint(&mut rt, &player);
+
+    let guess = rt.make_guess_spl(&vault, &player).unwrap().event::<GuessMade>();
+    assert_eq!((guess.referrer, guess.referral_cut), (None, 0));
+    assert!(!rt.exists(&earnings_pda(&Pubkey::default(), &mint)));
+}
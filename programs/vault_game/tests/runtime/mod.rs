@@ -0,0 +1,893 @@
+//! Runtime test harness.
+//!
+//! Runs the built program in LiteSVM, which executes CPIs, creates accounts and keeps transaction
+//! logs, so handlers that move tokens or emit events can be driven end to end through the same
+//! instructions a client would send.
+//!
+//! The program is loaded from `target/deploy/vault_game.so`, which only `anchor build` produces.
+//! Tests using this harness are `#[ignore]`d so a plain `cargo test` passes without it; run them
+//! with `anchor build && cargo test -- --include-ignored`.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::error::InstructionError;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_instruction, system_program};
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, spl_associated_token_account};
+use base64::Engine;
+use litesvm::LiteSVM;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+use vault_game::{
+    accounts, instruction, AccessMode, CreateVaultArgs, GlobalState, MegaSeason, MegaSeasonStatus, PlayerProfile,
+    SecretScheme, SettlementMode, Vault, VaultError,
+};
+
+pub const PROGRAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/vault_game.so");
+pub const START_TS: i64 = 1_700_000_000;
+pub const SOL: u64 = 1_000_000_000;
+pub const DAY: i64 = 86_400;
+
+pub const PIN: &[u8] = b"4821";
+pub const SALT: [u8; 32] = [9; 32];
+pub const NONCE: [u8; 32] = [7; 32];
+pub const DECIMALS: u8 = 6;
+
+/// Logs of a successful transaction.
+#[derive(Debug)]
+pub struct Logs(pub Vec<String>);
+
+impl Logs {
+    /// Every `E` the program emitted (`Program data:` lines whose discriminator is `E`'s).
+    pub fn events<E: AnchorDeserialize + Discriminator>(&self) -> Vec<E> {
+        self.0
+            .iter()
+            .filter_map(|line| line.strip_prefix("Program data: "))
+            .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(E::DISCRIMINATOR))
+            .map(|data| E::try_from_slice(&data[E::DISCRIMINATOR.len()..]).expect("event data"))
+            .collect()
+    }
+
+    /// The one `E` the program emitted.
+    pub fn event<E: AnchorDeserialize + Discriminator>(&self) -> E {
+        let mut events = self.events::<E>();
+        assert_eq!(events.len(), 1, "expected exactly one event");
+        events.remove(0)
+    }
+}
+
+/// The error a failed instruction surfaces as, for comparing with a `send` result.
+pub fn vault_err(index: u8, err: VaultError) -> TransactionError {
+    let code = anchor_lang::error::ERROR_CODE_OFFSET + err as u32;
+    // The compute budget instruction `send` prepends is instruction 0.
+    TransactionError::InstructionError(index + 1, InstructionError::Custom(code))
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &vault_game::ID).0
+}
+
+pub fn vault_pda(vault_id: u64) -> Pubkey {
+    pda(&[b"vault", &vault_id.to_le_bytes()])
+}
+
+pub fn vault_index_pda(vault_id: u64) -> Pubkey {
+    pda(&[b"vault_index", &(vault_id / 256).to_le_bytes()])
+}
+
+pub fn mega_season_pda(fee_mint: &Pubkey, season_id: u64) -> Pubkey {
+    pda(&[b"mega_season", fee_mint.as_ref(), &season_id.to_le_bytes()])
+}
+
+pub fn mint_stats_pda(fee_mint: &Pubkey) -> Pubkey {
+    pda(&[b"mint_stats", fee_mint.as_ref()])
+}
+
+pub fn player_pda(player: &Pubkey) -> Pubkey {
+    pda(&[b"player", player.as_ref()])
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::ID)
+}
+
+/// A claim-settled, salted vault over `PIN`, open for a day, with the protocol's default fees.
+pub fn vault_args(now: i64, fee_mint: Option<Pubkey>, prize: u64, base_fee: u64) -> CreateVaultArgs {
+    CreateVaultArgs {
+        end_ts: now + DAY,
+        secret_hash: solana_sha256_hasher::hashv(&[&SALT, PIN]).to_bytes(),
+        prize_amount: prize,
+        base_fee_amount: base_fee,
+        pin_len: PIN.len() as u8,
+        fee_mint,
+        secret_scheme: SecretScheme::SaltedSha256 as u8,
+        claim_window_slots: 0,
+        excluded_wallets: vec![],
+        settlement_mode: SettlementMode::Claim as u8,
+        hint_root: None,
+        fee_curve: None,
+        max_fee_amount: None,
+        fee_split: None,
+        max_attempts_per_player: 0,
+        cooldown_secs: 0,
+        access_mode: AccessMode::Open as u8,
+        gate_mint: None,
+        gate_min_amount: 0,
+        allowlist_root: None,
+        metadata: None,
+    }
+}
+
+pub fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: vault_game::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// A LiteSVM bank with the program deployed and the protocol set up through its admin
+/// instructions: `GlobalState`, `GlobalConfig`, `GlobalStats`, the mega vault and SOL season 0.
+pub struct Runtime {
+    pub svm: LiteSVM,
+    pub authority: Keypair,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runtime {
+    pub fn new() -> Runtime {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(vault_game::ID, PROGRAM)
+            .expect("target/deploy/vault_game.so (run `anchor build`)");
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = START_TS;
+        svm.set_sysvar(&clock);
+
+        let authority = Keypair::new();
+        svm.airdrop(&authority.pubkey(), 1_000 * SOL).unwrap();
+        let mut rt = Runtime { svm, authority };
+
+        let admin = rt.authority.pubkey();
+        rt.admin(ix(
+            accounts::InitializeGlobal {
+                global_state: pda(&[b"global"]),
+                mega_vault: pda(&[b"mega_vault"]),
+                authority: admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeGlobal {
+                skr_mint: Pubkey::new_unique(),
+            },
+        ));
+        // Any `SetGlobalConfig` instruction creates the config.
+        rt.admin(ix(
+            rt.config_accounts(),
+            instruction::SetReferee {
+                referee: Pubkey::new_unique(),
+            },
+        ));
+        rt.admin(ix(
+            accounts::InitGlobalStats {
+                global_state: pda(&[b"global"]),
+                global_stats: pda(&[b"stats"]),
+                sol_mint_stats: mint_stats_pda(&Pubkey::default()),
+                authority: admin,
+                payer: admin,
+                system_program: system_program::ID,
+            },
+            instruction::InitGlobalStats {},
+        ));
+        rt.admin(ix(
+            accounts::OpenMegaSeason {
+                global_state: pda(&[b"global"]),
+                mega_vault: pda(&[b"mega_vault"]),
+                mega_season: mega_season_pda(&Pubkey::default(), 0),
+                mega_vault_fee_ata: None,
+                authority: admin,
+                payer: admin,
+                system_program: system_program::ID,
+            },
+            instruction::OpenMegaSeason {
+                fee_mint: Pubkey::default(),
+            },
+        ));
+        rt
+    }
+
+    /// `SetGlobalConfig` accounts, signed and paid for by the protocol authority.
+    pub fn config_accounts(&self) -> accounts::SetGlobalConfig {
+        accounts::SetGlobalConfig {
+            global_state: pda(&[b"global"]),
+            global_config: pda(&[b"config"]),
+            authority: self.authority.pubkey(),
+            payer: self.authority.pubkey(),
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Send `ixs` as one transaction paid for by the first signer, with the maximum compute limit.
+    pub fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> std::result::Result<Logs, TransactionError> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        all.extend_from_slice(ixs);
+        let tx = Transaction::new_signed_with_payer(
+            &all,
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx);
+        // A fresh blockhash keeps a repeated transaction from being rejected as a duplicate.
+        self.svm.expire_blockhash();
+        result.map(|meta| Logs(meta.logs)).map_err(|failed| failed.err)
+    }
+
+    /// Send `ix` signed by the protocol authority; it must succeed.
+    pub fn admin(&mut self, ix: Instruction) -> Logs {
+        let authority = self.authority.insecure_clone();
+        self.send(&[ix], &[&authority]).expect("admin instruction")
+    }
+
+    /// A wallet funded with 100 SOL.
+    pub fn wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.svm.airdrop(&wallet.pubkey(), 100 * SOL).unwrap();
+        wallet
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Advance the clock by `slots` slots and `secs` seconds.
+    pub fn warp(&mut self, slots: u64, secs: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.slot += slots;
+        clock.unix_timestamp += secs;
+        self.svm.set_sysvar(&clock);
+    }
+
+    pub fn get<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self
+            .svm
+            .get_account(key)
+            .unwrap_or_else(|| panic!("missing account {key}"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("deserialize account")
+    }
+
+    pub fn exists(&self, key: &Pubkey) -> bool {
+        self.svm.get_account(key).is_some_and(|a| a.lamports > 0)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.svm.get_account(key).map_or(0, |a| a.lamports)
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        use spl_token_2022::extension::StateWithExtensions;
+
+        let account = self
+            .svm
+            .get_account(key)
+            .unwrap_or_else(|| panic!("missing account {key}"));
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .expect("token account")
+            .base
+            .amount
+    }
+
+    /// The open mega season for `fee_mint`.
+    pub fn open_season(&self, fee_mint: &Pubkey) -> Pubkey {
+        (0..)
+            .map(|id| mega_season_pda(fee_mint, id))
+            .find(|key| self.get::<MegaSeason>(key).status == MegaSeasonStatus::Open as u8)
+            .unwrap()
+    }
+
+    // -----------------
+    // Token-2022
+    // -----------------
+
+    /// A Token-2022 mint (mint authority: the protocol authority) with a transfer fee of `bps`
+    /// basis points, capped at `max_fee`.
+    pub fn create_fee_mint(&mut self, bps: u16, max_fee: u64) -> Pubkey {
+        use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+        let mint = Keypair::new();
+        let authority = self.authority.insecure_clone();
+        let len =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &authority.pubkey(),
+                &mint.pubkey(),
+                self.svm.minimum_balance_for_rent_exemption(len),
+                len as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                Some(&authority.pubkey()),
+                Some(&authority.pubkey()),
+                bps,
+                max_fee,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                &authority.pubkey(),
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ];
+        self.send(&ixs, &[&authority, &mint]).expect("create mint");
+        mint.pubkey()
+    }
+
+    /// `owner`'s associated token account for `mint`, created if needed.
+    pub fn ata(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let authority = self.authority.insecure_clone();
+        self.send(
+            &[spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &authority.pubkey(),
+                owner,
+                mint,
+                &spl_token_2022::ID,
+            )],
+            &[&authority],
+        )
+        .expect("create token account");
+        ata(owner, mint)
+    }
+
+    pub fn mint_to(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        let authority = self.authority.insecure_clone();
+        self.send(
+            &[spl_token_2022::instruction::mint_to_checked(
+                &spl_token_2022::ID,
+                mint,
+                account,
+                &authority.pubkey(),
+                &[],
+                amount,
+                DECIMALS,
+            )
+            .unwrap()],
+            &[&authority],
+        )
+        .expect("mint");
+    }
+
+    /// `owner`'s token account for `mint`, holding `amount` more tokens.
+    pub fn fund(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let account = self.ata(owner, mint);
+        self.mint_to(mint, &account, amount);
+        account
+    }
+
+    /// Register `mint` as a fee mint and open its mega season 0.
+    pub fn register_fee_mint(&mut self, mint: &Pubkey, min_prize: u64, min_base_fee: u64) {
+        let admin = self.authority.pubkey();
+        self.admin(ix(
+            accounts::SetFeeMintConfig {
+                global_state: pda(&[b"global"]),
+                fee_mint: *mint,
+                fee_mint_config: pda(&[b"fee_mint", mint.as_ref()]),
+                mint_stats: mint_stats_pda(mint),
+                authority: admin,
+                payer: admin,
+                system_program: system_program::ID,
+            },
+            instruction::SetFeeMintConfig {
+                enabled: true,
+                min_prize_amount: min_prize,
+                min_base_fee_amount: min_base_fee,
+            },
+        ));
+        let mega_vault_fee_ata = self.ata(&pda(&[b"mega_vault"]), mint);
+        self.admin(ix(
+            accounts::OpenMegaSeason {
+                global_state: pda(&[b"global"]),
+                mega_vault: pda(&[b"mega_vault"]),
+                mega_season: mega_season_pda(mint, 0),
+                mega_vault_fee_ata: Some(mega_vault_fee_ata),
+                authority: admin,
+                payer: admin,
+                system_program: system_program::ID,
+            },
+            instruction::OpenMegaSeason { fee_mint: *mint },
+        ));
+    }
+
+    // -----------------
+    // vault_game
+    // -----------------
+
+    /// The vault and creator index page the next `create_vault*` by `creator` writes.
+    fn next_vault(&self, creator: &Pubkey) -> (u64, Pubkey) {
+        let vault_id = self.get::<GlobalState>(&pda(&[b"global"])).vault_count;
+        let created = if self.exists(&player_pda(creator)) {
+            self.get::<PlayerProfile>(&player_pda(creator)).vaults_created
+        } else {
+            0
+        };
+        let page = vault_game::CreatorVaultIndex::page_of(created);
+        (vault_id, pda(&[b"creator_vaults", creator.as_ref(), &page.to_le_bytes()]))
+    }
+
+    /// `create_vault` in `args.fee_mint`, funded from `creator`'s associated token account.
+    pub fn create_vault(
+        &mut self,
+        creator: &Keypair,
+        args: CreateVaultArgs,
+    ) -> std::result::Result<(Pubkey, Logs), TransactionError> {
+        let mint = args.fee_mint.expect("token vault");
+        let (vault_id, creator_index) = self.next_vault(&creator.pubkey());
+        let vault = vault_pda(vault_id);
+        let logs = self.send(
+            &[ix(
+                accounts::CreateVault {
+                    global_state: pda(&[b"global"]),
+                    mega_vault: pda(&[b"mega_vault"]),
+                    vault,
+                    player_profile: player_pda(&creator.pubkey()),
+                    fee_mint: mint,
+                    creator_fee_ata: ata(&creator.pubkey(), &mint),
+                    vault_fee_ata: ata(&vault, &mint),
+                    vault_prize_ata: ata(&vault, &mint),
+                    mega_vault_fee_ata: ata(&pda(&[b"mega_vault"]), &mint),
+                    creator: creator.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    fee_mint_config: pda(&[b"fee_mint", mint.as_ref()]),
+                    global_config: pda(&[b"config"]),
+                    vault_index: vault_index_pda(vault_id),
+                    creator_index,
+                    global_stats: pda(&[b"stats"]),
+                    vault_metadata: args.metadata.is_some().then(|| pda(&[b"vault_meta", vault.as_ref()])),
+                },
+                instruction::CreateVault { args },
+            )],
+            &[creator],
+        )?;
+        Ok((vault, logs))
+    }
+
+    /// `create_vault_sol`; `args.fee_mint` must be `None`.
+    pub fn create_vault_sol(
+        &mut self,
+        creator: &Keypair,
+        args: CreateVaultArgs,
+    ) -> std::result::Result<(Pubkey, Logs), TransactionError> {
+        let (vault_id, creator_index) = self.next_vault(&creator.pubkey());
+        let vault = vault_pda(vault_id);
+        let logs = self.send(
+            &[ix(
+                accounts::CreateVaultSol {
+                    global_state: pda(&[b"global"]),
+                    vault,
+                    player_profile: player_pda(&creator.pubkey()),
+                    creator: creator.pubkey(),
+                    system_program: system_program::ID,
+                    global_config: pda(&[b"config"]),
+                    vault_index: vault_index_pda(vault_id),
+                    creator_index,
+                    global_stats: pda(&[b"stats"]),
+                    vault_metadata: args.metadata.is_some().then(|| pda(&[b"vault_meta", vault.as_ref()])),
+                },
+                instruction::CreateVaultSol { args },
+            )],
+            &[creator],
+        )?;
+        Ok((vault, logs))
+    }
+
+    /// `touch_player` by `player`, creating their profile.
+    pub fn touch_player(&mut self, player: &Keypair) {
+        self.send(
+            &[ix(
+                accounts::TouchPlayer {
+                    player_profile: player_pda(&player.pubkey()),
+                    player: player.pubkey(),
+                    system_program: system_program::ID,
+                },
+                instruction::TouchPlayer {},
+            )],
+            &[player],
+        )
+        .expect("touch_player");
+    }
+
+    /// `bind_referrer` of `referrer` (who must have a profile) by `player`.
+    pub fn bind_referrer(&mut self, player: &Keypair, referrer: &Pubkey) {
+        self.send(
+            &[ix(
+                accounts::BindReferrer {
+                    player_profile: player_pda(&player.pubkey()),
+                    referrer_profile: player_pda(referrer),
+                    player: player.pubkey(),
+                    system_program: system_program::ID,
+                },
+                instruction::BindReferrer { referrer: *referrer },
+            )],
+            &[player],
+        )
+        .expect("bind_referrer");
+    }
+
+    /// `make_guess_spl` by `player` from their associated token account, with a wrong guess.
+    ///
+    /// Passes the referral accounts when `player` has a bound referrer.
+    pub fn make_guess_spl(&mut self, vault: &Pubkey, player: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        let v = self.get::<Vault>(vault);
+        let mint = v.fee_mint;
+        let key = player.pubkey();
+        let referrer = if self.exists(&player_pda(&key)) {
+            self.get::<PlayerProfile>(&player_pda(&key)).referrer
+        } else {
+            None
+        };
+        self.send(
+            &[ix(
+                accounts::MakeGuessSpl {
+                    vault: *vault,
+                    mega_vault: pda(&[b"mega_vault"]),
+                    player_profile: player_pda(&key),
+                    attempt_record: pda(&[b"attempt", vault.as_ref(), key.as_ref()]),
+                    guess_record: pda(&[b"guess", vault.as_ref(), &v.attempt_count.to_le_bytes()]),
+                    player: key,
+                    fee_mint: mint,
+                    player_fee_ata: ata(&key, &mint),
+                    mega_vault_fee_ata: ata(&pda(&[b"mega_vault"]), &mint),
+                    vault_fee_ata: ata(vault, &mint),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    mega_season: self.open_season(&mint),
+                    fee_mint_config: pda(&[b"fee_mint", mint.as_ref()]),
+                    treasury: None,
+                    treasury_fee_ata: None,
+                    creator_fee_ata: None,
+                    global_config: pda(&[b"config"]),
+                    referrer_profile: referrer.map(|r| player_pda(&r)),
+                    referrer_fee_ata: referrer.map(|r| ata(&r, &mint)),
+                    referral_earnings: referrer.map(|r| pda(&[b"referral", r.as_ref(), mint.as_ref()])),
+                    gate_token_account: None,
+                    global_stats: pda(&[b"stats"]),
+                    mint_stats: mint_stats_pda(&mint),
+                },
+                instruction::MakeGuessSpl {
+                    guess: [0; 32],
+                    allowlist_proof: vec![],
+                },
+            )],
+            &[player],
+        )
+    }
+
+    /// `make_guess_sol` by `player`, with a wrong guess.
+    ///
+    /// Passes the referral accounts when `player` has a bound referrer.
+    pub fn make_guess_sol(&mut self, vault: &Pubkey, player: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        let v = self.get::<Vault>(vault);
+        let key = player.pubkey();
+        let referrer = if self.exists(&player_pda(&key)) {
+            self.get::<PlayerProfile>(&player_pda(&key)).referrer
+        } else {
+            None
+        };
+        self.send(
+            &[ix(
+                accounts::MakeGuessSol {
+                    vault: *vault,
+                    mega_vault: pda(&[b"mega_vault"]),
+                    player_profile: player_pda(&key),
+                    attempt_record: pda(&[b"attempt", vault.as_ref(), key.as_ref()]),
+                    guess_record: pda(&[b"guess", vault.as_ref(), &v.attempt_count.to_le_bytes()]),
+                    player: key,
+                    system_program: system_program::ID,
+                    mega_season: self.open_season(&Pubkey::default()),
+                    treasury: None,
+                    vault_creator: None,
+                    global_config: pda(&[b"config"]),
+                    referrer_profile: referrer.map(|r| player_pda(&r)),
+                    referrer,
+                    gate_token_account: None,
+                    global_stats: pda(&[b"stats"]),
+                    mint_stats: mint_stats_pda(&Pubkey::default()),
+                },
+                instruction::MakeGuessSol {
+                    guess: [0; 32],
+                    allowlist_proof: vec![],
+                },
+            )],
+            &[player],
+        )
+    }
+
+    /// `commit_claim` to `PIN` by `player`.
+    pub fn commit_claim(&mut self, vault: &Pubkey, player: &Keypair) {
+        let key = player.pubkey();
+        self.send(
+            &[ix(
+                accounts::CommitClaim {
+                    vault: *vault,
+                    claim_commitment: pda(&[b"claim", vault.as_ref(), key.as_ref()]),
+                    player: key,
+                    system_program: system_program::ID,
+                    global_config: pda(&[b"config"]),
+                },
+                instruction::CommitClaim {
+                    commitment: solana_sha256_hasher::hashv(&[PIN, key.as_ref(), &NONCE]).to_bytes(),
+                },
+            )],
+            &[player],
+        )
+        .expect("commit_claim");
+    }
+
+    /// `reveal_salt` by the creator (the vault must have expired).
+    pub fn reveal_salt(&mut self, vault: &Pubkey, creator: &Keypair) {
+        self.send(
+            &[ix(
+                accounts::RevealSalt {
+                    vault: *vault,
+                    creator: creator.pubkey(),
+                    global_config: pda(&[b"config"]),
+                },
+                instruction::RevealSalt {
+                    salt: SALT,
+                    pin: PIN.to_vec(),
+                    hint_salts: vec![],
+                },
+            )],
+            &[creator],
+        )
+        .expect("reveal_salt");
+    }
+
+    /// `claim_win` revealing the `commit_claim` by `player`.
+    pub fn claim_win(&mut self, vault: &Pubkey, player: &Keypair) -> Logs {
+        let key = player.pubkey();
+        let vault_id = self.get::<Vault>(vault).vault_id;
+        self.send(
+            &[ix(
+                accounts::ClaimWin {
+                    vault: *vault,
+                    claim_commitment: pda(&[b"claim", vault.as_ref(), key.as_ref()]),
+                    attempt_record: pda(&[b"attempt", vault.as_ref(), key.as_ref()]),
+                    player_profile: player_pda(&key),
+                    displaced_profile: None,
+                    player: key,
+                    system_program: system_program::ID,
+                    gate_token_account: None,
+                    global_config: pda(&[b"config"]),
+                    vault_index: vault_index_pda(vault_id),
+                    global_stats: pda(&[b"stats"]),
+                },
+                instruction::ClaimWin {
+                    secret: PIN.to_vec(),
+                    nonce: NONCE,
+                    allowlist_proof: vec![],
+                },
+            )],
+            &[player],
+        )
+        .expect("claim_win")
+    }
+
+    /// Settle a salted claim-mode vault for `player`, who has made an attempt: commit, let the
+    /// vault expire, reveal the salt, claim, and wait out the claim window. Returns the
+    /// `claim_win` logs.
+    pub fn win(&mut self, vault: &Pubkey, creator: &Keypair, player: &Keypair) -> Logs {
+        self.commit_claim(vault, player);
+        let end_ts = self.get::<Vault>(vault).end_ts;
+        let now = self.now();
+        self.warp(1_000, end_ts - now + 1);
+        self.reveal_salt(vault, creator);
+        let logs = self.claim_win(vault, player);
+        self.warp(1_000, DAY + 1);
+        logs
+    }
+
+    /// Let the vault expire and reveal its salt, so `reclaim_prize*` can run.
+    pub fn expire(&mut self, vault: &Pubkey, creator: &Keypair) {
+        let end_ts = self.get::<Vault>(vault).end_ts;
+        let now = self.now();
+        self.warp(1_000, end_ts - now + 1);
+        self.reveal_salt(vault, creator);
+    }
+
+    /// `claim_prize` by the winner, to their associated token account.
+    pub fn claim_prize(&mut self, vault: &Pubkey, winner: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        let mint = self.get::<Vault>(vault).fee_mint;
+        self.send(
+            &[ix(
+                accounts::ClaimPrize {
+                    vault: *vault,
+                    mega_vault: pda(&[b"mega_vault"]),
+                    fee_mint: mint,
+                    vault_fee_ata: ata(vault, &mint),
+                    vault_prize_ata: ata(vault, &mint),
+                    winner_fee_ata: ata(&winner.pubkey(), &mint),
+                    winner: winner.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    fee_mint_config: pda(&[b"fee_mint", mint.as_ref()]),
+                    global_config: pda(&[b"config"]),
+                    mint_stats: mint_stats_pda(&mint),
+                },
+                instruction::ClaimPrize {},
+            )],
+            &[winner],
+        )
+    }
+
+    /// `reclaim_prize` by the creator, to their associated token account.
+    pub fn reclaim_prize(&mut self, vault: &Pubkey, creator: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        let v = self.get::<Vault>(vault);
+        let mint = v.fee_mint;
+        self.send(
+            &[ix(
+                accounts::ReclaimPrize {
+                    vault: *vault,
+                    mega_vault: pda(&[b"mega_vault"]),
+                    fee_mint: mint,
+                    vault_fee_ata: ata(vault, &mint),
+                    vault_prize_ata: ata(vault, &mint),
+                    creator_fee_ata: ata(&creator.pubkey(), &mint),
+                    mega_vault_fee_ata: ata(&pda(&[b"mega_vault"]), &mint),
+                    creator: creator.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    mega_season: self.open_season(&mint),
+                    fee_mint_config: pda(&[b"fee_mint", mint.as_ref()]),
+                    global_config: pda(&[b"config"]),
+                    vault_index: vault_index_pda(v.vault_id),
+                    global_stats: pda(&[b"stats"]),
+                    mint_stats: mint_stats_pda(&mint),
+                },
+                instruction::ReclaimPrize {},
+            )],
+            &[creator],
+        )
+    }
+
+    /// `claim_prize_sol` by the winner.
+    pub fn claim_prize_sol(&mut self, vault: &Pubkey, winner: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        self.send(
+            &[ix(
+                accounts::ClaimPrizeSol {
+                    vault: *vault,
+                    winner: winner.pubkey(),
+                    global_config: pda(&[b"config"]),
+                    mint_stats: mint_stats_pda(&Pubkey::default()),
+                },
+                instruction::ClaimPrizeSol {},
+            )],
+            &[winner],
+        )
+    }
+
+    /// `reclaim_prize_sol` by the creator.
+    pub fn reclaim_prize_sol(&mut self, vault: &Pubkey, creator: &Keypair) -> std::result::Result<Logs, TransactionError> {
+        let vault_id = self.get::<Vault>(vault).vault_id;
+        self.send(
+            &[ix(
+                accounts::ReclaimPrizeSol {
+                    vault: *vault,
+                    mega_vault: pda(&[b"mega_vault"]),
+                    creator: creator.pubkey(),
+                    mega_season: self.open_season(&Pubkey::default()),
+                    global_config: pda(&[b"config"]),
+                    vault_index: vault_index_pda(vault_id),
+                    global_stats: pda(&[b"stats"]),
+                    mint_stats: mint_stats_pda(&Pubkey::default()),
+                },
+                instruction::ReclaimPrizeSol {},
+            )],
+            &[creator],
+        )
+    }
+
+    /// `add_reward` of `amount` `reward_mint` tokens from the creator's associated token account.
+    pub fn add_reward(
+        &mut self,
+        vault: &Pubkey,
+        creator: &Keypair,
+        reward_mint: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<Logs, TransactionError> {
+        self.send(
+            &[ix(
+                accounts::AddReward {
+                    vault: *vault,
+                    reward: pda(&[b"reward", vault.as_ref(), reward_mint.as_ref()]),
+                    reward_mint: *reward_mint,
+                    creator_reward_ata: ata(&creator.pubkey(), reward_mint),
+                    vault_reward_ata: ata(vault, reward_mint),
+                    creator: creator.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    global_config: pda(&[b"config"]),
+                    global_stats: pda(&[b"stats"]),
+                },
+                instruction::AddReward { amount },
+            )],
+            &[creator],
+        )
+    }
+
+    /// `claim_reward` by the winner.
+    pub fn claim_reward(
+        &mut self,
+        vault: &Pubkey,
+        winner: &Keypair,
+        reward_mint: &Pubkey,
+    ) -> std::result::Result<Logs, TransactionError> {
+        self.send(
+            &[ix(
+                accounts::ClaimReward {
+                    vault: *vault,
+                    reward: pda(&[b"reward", vault.as_ref(), reward_mint.as_ref()]),
+                    reward_mint: *reward_mint,
+                    vault_reward_ata: ata(vault, reward_mint),
+                    winner_reward_ata: ata(&winner.pubkey(), reward_mint),
+                    winner: winner.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    global_config: pda(&[b"config"]),
+                    global_stats: pda(&[b"stats"]),
+                },
+                instruction::ClaimReward {},
+            )],
+            &[winner],
+        )
+    }
+
+    /// `reclaim_reward` by the creator.
+    pub fn reclaim_reward(
+        &mut self,
+        vault: &Pubkey,
+        creator: &Keypair,
+        reward_mint: &Pubkey,
+    ) -> std::result::Result<Logs, TransactionError> {
+        self.send(
+            &[ix(
+                accounts::ReclaimReward {
+                    vault: *vault,
+                    reward: pda(&[b"reward", vault.as_ref(), reward_mint.as_ref()]),
+                    reward_mint: *reward_mint,
+                    vault_reward_ata: ata(vault, reward_mint),
+                    creator_reward_ata: ata(&creator.pubkey(), reward_mint),
+                    creator: creator.pubkey(),
+                    token_program: spl_token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    system_program: system_program::ID,
+                    global_config: pda(&[b"config"]),
+                    global_stats: pda(&[b"stats"]),
+                },
+                instruction::ReclaimReward {},
+            )],
+            &[creator],
+        )
+    }
+}
@@ -0,0 +1,161 @@
+//! The creator and their declared associates may not win their own vault.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use vault_game::{accounts, instruction, ClaimCommitment, Vault, VaultError};
+
+const PIN: &[u8] = b"4821";
+const NONCE: [u8; 32] = [7; 32];
+
+struct Game {
+    svm: Svm,
+    p: Protocol,
+    creator: Pubkey,
+    vault: Pubkey,
+}
+
+fn game() -> Game {
+    let mut svm = Svm::new();
+    let p = Protocol::setup(&mut svm);
+    let creator = svm.wallet();
+    let vault = p.legacy_sol_vault(&mut svm, &creator, PIN, SOL / 100, SOL);
+    Game { svm, p, creator, vault }
+}
+
+impl Game {
+    /// A wallet the creator declared as an associate.
+    fn associate(&mut self) -> Pubkey {
+        let associate = self.svm.wallet();
+        self.svm.update::<Vault>(&self.vault, |v| {
+            v.excluded_wallets[v.excluded_wallet_count as usize] = associate;
+            v.excluded_wallet_count += 1;
+        });
+        associate
+    }
+
+    fn commit_claim(&mut self, player: &Pubkey) -> std::result::Result<(), ProgramError> {
+        let commitment = solana_sha256_hasher::hashv(&[PIN, player.as_ref(), &NONCE]).to_bytes();
+        self.svm.send_ix(ix(
+            accounts::CommitClaim {
+                vault: self.vault,
+                claim_commitment: pda(&[b"claim", self.vault.as_ref(), player.as_ref()]),
+                player: *player,
+                system_program: system_program::ID,
+                global_config: self.p.global_config,
+            },
+            instruction::CommitClaim { commitment },
+        ))
+    }
+
+    /// Write `player`'s claim commitment as an earlier `commit_claim` would have left it
+    /// (`commit_claim` refuses to write one for the self-dealers).
+    fn put_commitment(&mut self, player: &Pubkey, commitment: [u8; 32]) {
+        let seeds: &[&[u8]] = &[b"claim", self.vault.as_ref(), player.as_ref()];
+        let cc = ClaimCommitment {
+            vault: self.vault,
+            player: *player,
+            commitment,
+            slot: self.svm.clock().slot,
+            bump: bump(seeds),
+        };
+        self.svm.put(pda(seeds), &cc, 8 + ClaimCommitment::LEN);
+    }
+
+    fn claim_win(&mut self, player: &Pubkey) -> std::result::Result<(), ProgramError> {
+        let vault_id = self.svm.get::<Vault>(&self.vault).vault_id;
+        self.svm.send_ix(ix(
+            accounts::ClaimWin {
+                vault: self.vault,
+                claim_commitment: pda(&[b"claim", self.vault.as_ref(), player.as_ref()]),
+                attempt_record: pda(&[b"attempt", self.vault.as_ref(), player.as_ref()]),
+                player_profile: pda(&[b"player", player.as_ref()]),
+                displaced_profile: None,
+                player: *player,
+                system_program: system_program::ID,
+                gate_token_account: None,
+                global_config: self.p.global_config,
+                vault_index: vault_index_pda(vault_id),
+                global_stats: self.p.global_stats,
+            },
+            instruction::ClaimWin {
+                secret: PIN.to_vec(),
+                nonce: NONCE,
+                allowlist_proof: vec![],
+            },
+        ))
+    }
+
+    /// `player` has made an attempt and holds a stale commitment that `commit_claim` would replace.
+    fn prepare(&mut self, player: &Pubkey) {
+        self.p.put_attempt(&mut self.svm, &self.vault, player);
+        self.put_commitment(player, [0; 32]);
+    }
+
+    /// `player` committed to the right PIN and waited out the reveal delay.
+    fn prepare_claim(&mut self, player: &Pubkey) {
+        self.prepare(player);
+        let commitment = solana_sha256_hasher::hashv(&[PIN, player.as_ref(), &NONCE]).to_bytes();
+        self.put_commitment(player, commitment);
+        self.svm.warp(10, 5);
+    }
+}
+
+#[test]
+fn creator_cannot_commit_claim() {
+    let mut g = game();
+    let creator = g.creator;
+    g.prepare(&creator);
+    assert_eq!(g.commit_claim(&creator), Err(vault_err(VaultError::SelfDealingBlocked)));
+}
+
+#[test]
+fn excluded_wallet_cannot_commit_claim() {
+    let mut g = game();
+    let associate = g.associate();
+    g.prepare(&associate);
+    assert_eq!(g.commit_claim(&associate), Err(vault_err(VaultError::SelfDealingBlocked)));
+}
+
+#[test]
+fn creator_cannot_claim_win() {
+    let mut g = game();
+    let creator = g.creator;
+    g.prepare_claim(&creator);
+    assert_eq!(g.claim_win(&creator), Err(vault_err(VaultError::SelfDealingBlocked)));
+    assert_eq!(g.svm.get::<Vault>(&g.vault).winner, None);
+}
+
+#[test]
+fn excluded_wallet_cannot_claim_win() {
+    let mut g = game();
+    let associate = g.associate();
+    g.prepare_claim(&associate);
+    assert_eq!(g.claim_win(&associate), Err(vault_err(VaultError::SelfDealingBlocked)));
+    assert_eq!(g.svm.get::<Vault>(&g.vault).winner, None);
+}
+
+#[test]
+fn other_player_can_commit_and_claim() {
+    let mut g = game();
+    g.associate();
+    let player = g.svm.wallet();
+    g.prepare(&player);
+    g.commit_claim(&player).unwrap();
+    g.svm.warp(10, 5);
+    g.claim_win(&player).unwrap();
+    assert_eq!(g.svm.get::<Vault>(&g.vault).winner, Some(player));
+}
+
+#[test]
+fn declared_associates_do_not_shift_the_vault_layout() {
+    let mut g = game();
+    let empty = g.svm.get::<Vault>(&g.vault).try_to_vec().unwrap();
+    for _ in 0..8 {
+        g.associate();
+    }
+    let full = g.svm.get::<Vault>(&g.vault).try_to_vec().unwrap();
+    assert_eq!(full.len(), empty.len());
+}
@@ -0,0 +1,251 @@
+//! `GlobalStats` / `MintStats` move by exactly what each instruction emits.
+//!
+//! Every test runs the real handlers in the runtime (see `runtime`), parses the `VaultCreated`,
+//! `GuessMade`, `VaultWon`, `PrizeClaimed`, `PrizeReclaimed`, `RewardAdded` and `RewardClaimed`
+//! events from the program logs, and checks the stats deltas against them.
+
+mod runtime;
+
+use anchor_lang::prelude::*;
+use runtime::{Runtime, DAY, SOL};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use vault_game::{
+    GlobalStats, GuessMade, MintStats, PrizeClaimed, PrizeReclaimed, RewardAdded, RewardClaimed, VaultCreated,
+    VaultWon,
+};
+
+const REWARD: u64 = 250_000_000;
+
+struct Stats {
+    global: GlobalStats,
+    mint: MintStats,
+}
+
+struct Game {
+    rt: Runtime,
+    creator: Keypair,
+    player: Keypair,
+    /// Fee currency of the vaults, `Pubkey::default()` for SOL.
+    fee_mint: Pubkey,
+}
+
+/// A SOL game with a funded creator and player.
+fn game() -> Game {
+    let mut rt = Runtime::new();
+    Game {
+        creator: rt.wallet(),
+        player: rt.wallet(),
+        rt,
+        fee_mint: Pubkey::default(),
+    }
+}
+
+/// A game in a registered Token-2022 mint without transfer fees, with funded creator and player.
+fn token_game() -> Game {
+    let mut rt = Runtime::new();
+    let mint = rt.create_fee_mint(0, 0);
+    rt.register_fee_mint(&mint, 0, 0);
+    let mut g = Game {
+        creator: rt.wallet(),
+        player: rt.wallet(),
+        rt,
+        fee_mint: mint,
+    };
+    g.rt.fund(&g.creator.pubkey(), &mint, 100 * SOL);
+    g.rt.fund(&g.player.pubkey(), &mint, 100 * SOL);
+    g
+}
+
+impl Game {
+    fn is_sol(&self) -> bool {
+        self.fee_mint == Pubkey::default()
+    }
+
+    /// `owner`'s lamports in a SOL game, else its associated token account balance.
+    fn balance(&self, owner: &Pubkey) -> u64 {
+        if self.is_sol() {
+            self.rt.lamports(owner)
+        } else {
+            self.rt.token_amount(&runtime::ata(owner, &self.fee_mint))
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        Stats {
+            global: self.rt.get::<GlobalStats>(&runtime::pda(&[b"stats"])),
+            mint: self.rt.get::<MintStats>(&runtime::mint_stats_pda(&self.fee_mint)),
+        }
+    }
+
+    /// Create a vault with a `SOL` prize; checks its `VaultCreated` against the stats.
+    fn create(&mut self) -> Pubkey {
+        let before = self.stats();
+        let fee_mint = (!self.is_sol()).then_some(self.fee_mint);
+        let args = runtime::vault_args(self.rt.now(), fee_mint, SOL, SOL / 100);
+        let (vault, logs) = if self.is_sol() {
+            self.rt.create_vault_sol(&self.creator, args)
+        } else {
+            self.rt.create_vault(&self.creator, args)
+        }
+        .unwrap();
+
+        let created = logs.event::<VaultCreated>();
+        assert_eq!((created.vault, created.is_sol_fee), (vault, self.is_sol()));
+        let after = self.stats();
+        assert_eq!(after.global.vaults_created, before.global.vaults_created + 1);
+        assert_eq!(after.global.active_vaults, before.global.active_vaults + 1);
+        vault
+    }
+
+    /// `n` wrong guesses by the player; checks their `GuessMade` events against the stats.
+    fn guess(&mut self, vault: &Pubkey, n: u64) {
+        let before = self.stats();
+        let guesses: Vec<GuessMade> = (0..n)
+            .map(|_| {
+                let logs = if self.is_sol() {
+                    self.rt.make_guess_sol(vault, &self.player)
+                } else {
+                    self.rt.make_guess_spl(vault, &self.player)
+                };
+                logs.unwrap().event::<GuessMade>()
+            })
+            .collect();
+
+        let after = self.stats();
+        assert_eq!(after.global.total_attempts, before.global.total_attempts + n);
+        assert_eq!(
+            after.mint.fees_collected - before.mint.fees_collected,
+            guesses.iter().map(|g| g.fee).sum::<u64>()
+        );
+        assert_eq!(
+            after.mint.mega_inflow - before.mint.mega_inflow,
+            guesses.iter().map(|g| g.mega_cut).sum::<u64>()
+        );
+        assert_eq!(after.global.active_vaults, before.global.active_vaults);
+    }
+
+    /// Settle `vault` for the player; checks the `VaultWon` against `active_vaults`.
+    fn win(&mut self, vault: &Pubkey) {
+        let before = self.stats();
+        let won = self.rt.win(vault, &self.creator, &self.player).event::<VaultWon>();
+        assert_eq!((won.vault, won.winner), (*vault, self.player.pubkey()));
+        assert_eq!(self.stats().global.active_vaults, before.global.active_vaults - 1);
+    }
+
+    fn claim_prize(&mut self, vault: &Pubkey) -> PrizeClaimed {
+        let logs = if self.is_sol() {
+            self.rt.claim_prize_sol(vault, &self.player)
+        } else {
+            self.rt.claim_prize(vault, &self.player)
+        };
+        logs.unwrap().event::<PrizeClaimed>()
+    }
+
+    fn reclaim_prize(&mut self, vault: &Pubkey) -> PrizeReclaimed {
+        let logs = if self.is_sol() {
+            self.rt.reclaim_prize_sol(vault, &self.creator)
+        } else {
+            self.rt.reclaim_prize(vault, &self.creator)
+        };
+        logs.unwrap().event::<PrizeReclaimed>()
+    }
+}
+
+/// Create two vaults, guess on both, then claim one and reclaim the other, checking every step's
+/// events against the stats and the balances they were paid from.
+fn settle_both_ways(g: &mut Game) {
+    let won = g.create();
+    let reclaimed = g.create();
+    g.guess(&won, 3);
+    g.guess(&reclaimed, 2);
+
+    g.win(&won);
+    let before = (g.stats(), g.balance(&won));
+    let claimed = g.claim_prize(&won);
+    assert_eq!((claimed.vault, claimed.winner), (won, g.player.pubkey()));
+    assert_eq!(before.1 - g.balance(&won), claimed.amount);
+    let (before, after) = (before.0, g.stats());
+    assert_eq!(after.mint.prizes_paid - before.mint.prizes_paid, claimed.amount);
+    assert_eq!(after.mint.reclaimed, before.mint.reclaimed);
+    assert_eq!(after.mint.mega_inflow, before.mint.mega_inflow);
+    assert_eq!(after.global.active_vaults, before.global.active_vaults);
+
+    let creator = g.creator.insecure_clone();
+    g.rt.expire(&reclaimed, &creator);
+    let mega = runtime::pda(&[b"mega_vault"]);
+    let before = (g.stats(), g.balance(&mega));
+    let returned = g.reclaim_prize(&reclaimed);
+    assert_eq!((returned.vault, returned.creator), (reclaimed, creator.pubkey()));
+    assert!(returned.mega_cut > 0);
+    assert_eq!(g.balance(&mega) - before.1, returned.mega_cut);
+    let (before, after) = (before.0, g.stats());
+    assert_eq!(after.mint.reclaimed - before.mint.reclaimed, returned.amount);
+    assert_eq!(after.mint.mega_inflow - before.mint.mega_inflow, returned.mega_cut);
+    assert_eq!(after.mint.prizes_paid, before.mint.prizes_paid);
+    assert_eq!(after.global.active_vaults, before.global.active_vaults - 1);
+
+    assert_eq!(after.global.vaults_created, 2);
+    assert_eq!(after.global.active_vaults, 0);
+    assert_eq!(after.global.total_attempts, 5);
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn sol_stats_follow_the_emitted_events() {
+    let mut g = game();
+    settle_both_ways(&mut g);
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn token_stats_follow_the_emitted_events() {
+    let mut g = token_game();
+    let sol = g.rt.get::<MintStats>(&runtime::mint_stats_pda(&Pubkey::default()));
+    settle_both_ways(&mut g);
+
+    let after = g.rt.get::<MintStats>(&runtime::mint_stats_pda(&Pubkey::default()));
+    assert_eq!(
+        (after.fees_collected, after.prizes_paid, after.reclaimed, after.mega_inflow),
+        (sol.fees_collected, sol.prizes_paid, sol.reclaimed, sol.mega_inflow)
+    );
+    assert!(g.stats().mint.fees_collected > 0);
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn reward_counters_follow_the_emitted_events() {
+    let mut g = game();
+    let reward_mint = g.rt.create_fee_mint(0, 0);
+    let creator = g.creator.insecure_clone();
+    g.rt.fund(&creator.pubkey(), &reward_mint, 2 * REWARD);
+    let won = g.create();
+    let reclaimed = g.create();
+
+    for vault in [won, reclaimed] {
+        let before = g.stats().global;
+        let added = g.rt.add_reward(&vault, &creator, &reward_mint, REWARD).unwrap().event::<RewardAdded>();
+        assert_eq!((added.vault, added.mint, added.amount), (vault, reward_mint, REWARD));
+        assert_eq!(g.stats().global.rewards_added, before.rewards_added + 1);
+    }
+
+    g.guess(&won, 1);
+    g.win(&won);
+    let player = g.player.insecure_clone();
+    let before = g.stats().global;
+    let claimed = g.rt.claim_reward(&won, &player, &reward_mint).unwrap().event::<RewardClaimed>();
+    assert_eq!((claimed.to, claimed.amount), (player.pubkey(), REWARD));
+    let after = g.stats().global;
+    assert_eq!(after.rewards_claimed, before.rewards_claimed + 1);
+    assert_eq!(after.rewards_reclaimed, before.rewards_reclaimed);
+
+    g.rt.expire(&reclaimed, &creator);
+    g.rt.warp(1_000, DAY + 1);
+    let before = g.stats().global;
+    let returned = g.rt.reclaim_reward(&reclaimed, &creator, &reward_mint).unwrap().event::<RewardClaimed>();
+    assert_eq!((returned.to, returned.amount), (creator.pubkey(), REWARD));
+    let after = g.stats().global;
+    assert_eq!(after.rewards_reclaimed, before.rewards_reclaimed + 1);
+    assert_eq!(after.rewards_claimed, before.rewards_claimed);
+    assert_eq!((after.rewards_added, after.rewards_claimed, after.rewards_reclaimed), (2, 1, 1));
+}
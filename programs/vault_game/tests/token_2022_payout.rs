@@ -0,0 +1,257 @@
+//! Token vault payouts for a Token-2022 mint with a transfer fee.
+//!
+//! The `handler_*` tests run `create_vault`, `make_guess_spl`, `claim_prize` and `reclaim_prize` in
+//! the runtime (see `runtime`), so every transfer goes through `transfer_fee_tokens`' CPI. The
+//! others send the same transfers off-chain at the top level, from one vault token account holding
+//! both the prize and the winner pool.
+
+mod common;
+mod runtime;
+
+use anchor_lang::prelude::*;
+use common::*;
+use runtime::Runtime;
+use solana_signer::Signer;
+use spl_token_2022::error::TokenError;
+use vault_game::{fees, GuessMade, MegaSeason, PrizeClaimed, PrizeReclaimed, Vault};
+
+/// 1.5% transfer fee.
+const FEE_BPS: u16 = 150;
+const MAX_FEE: u64 = 5_000_000;
+
+struct Payout {
+    svm: Svm,
+    mint: FeeMint,
+    /// The vault PDA, owner of `vault_ata`.
+    vault: Pubkey,
+    vault_ata: Pubkey,
+    /// Prize as booked at create time (net of the deposit's transfer fee).
+    prize_amount: u64,
+}
+
+/// A vault holding a deposited prize plus the winner shares of `attempts` attempt fees.
+fn funded_vault(prize: u64, attempts: u64, winner_share: u64) -> Payout {
+    let mut svm = Svm::new();
+    let mint = FeeMint::create(&mut svm, FEE_BPS, MAX_FEE);
+    let vault = vault_pda(0);
+    let vault_ata = mint.account(&mut svm, &vault);
+
+    let creator = svm.wallet();
+    let creator_ata = mint.account(&mut svm, &creator);
+    mint.mint_to(&mut svm, &creator_ata, prize);
+    mint.transfer(&mut svm, &creator_ata, &vault_ata, &creator, prize).unwrap();
+    let prize_amount = svm.token_amount(&vault_ata);
+    assert!(prize_amount < prize, "deposit should be charged a transfer fee");
+
+    let player = svm.wallet();
+    let player_ata = mint.account(&mut svm, &player);
+    mint.mint_to(&mut svm, &player_ata, attempts * winner_share);
+    for _ in 0..attempts {
+        mint.transfer(&mut svm, &player_ata, &vault_ata, &player, winner_share).unwrap();
+    }
+
+    Payout {
+        svm,
+        mint,
+        vault,
+        vault_ata,
+        prize_amount,
+    }
+}
+
+#[test]
+fn claim_pays_prize_and_pool_from_one_balance() {
+    let mut p = funded_vault(1_000 * SOL, 25, 40_000_000);
+    let balance = p.svm.token_amount(&p.vault_ata);
+    let pool = fees::vault_pool(balance, p.prize_amount).unwrap();
+    assert!(pool > 0);
+
+    let winner = p.svm.wallet();
+    let winner_ata = p.mint.account(&mut p.svm, &winner);
+    p.mint
+        .transfer(&mut p.svm, &p.vault_ata, &winner_ata, &p.vault, p.prize_amount)
+        .unwrap();
+    p.mint.transfer(&mut p.svm, &p.vault_ata, &winner_ata, &p.vault, pool).unwrap();
+
+    assert_eq!(p.svm.token_amount(&p.vault_ata), 0);
+    let received = p.svm.token_amount(&winner_ata);
+    assert!(received < balance, "payouts should be charged a transfer fee");
+    assert!(received >= balance - 2 * MAX_FEE);
+}
+
+#[test]
+fn stale_balance_overdraws_after_prize_transfer() {
+    let mut p = funded_vault(1_000 * SOL, 25, 40_000_000);
+    let balance = p.svm.token_amount(&p.vault_ata);
+
+    let winner = p.svm.wallet();
+    let winner_ata = p.mint.account(&mut p.svm, &winner);
+    p.mint
+        .transfer(&mut p.svm, &p.vault_ata, &winner_ata, &p.vault, p.prize_amount)
+        .unwrap();
+
+    // The whole pre-transfer balance is no longer there to pay as "pool".
+    assert_eq!(
+        p.mint.transfer(&mut p.svm, &p.vault_ata, &winner_ata, &p.vault, balance),
+        Err(TokenError::InsufficientFunds.into())
+    );
+}
+
+#[test]
+fn reclaim_splits_pool_and_empties_the_vault() {
+    let mut p = funded_vault(500 * SOL, 7, 13_333_333);
+    let balance = p.svm.token_amount(&p.vault_ata);
+    let pool = fees::vault_pool(balance, p.prize_amount).unwrap();
+    let creator_cut = pool / 2;
+    let mega_cut = pool - creator_cut;
+
+    let creator = p.svm.wallet();
+    let creator_ata = p.mint.account(&mut p.svm, &creator);
+    let mega_ata = p.mint.account(&mut p.svm, &pda(&[b"mega_vault"]));
+    p.mint
+        .transfer(&mut p.svm, &p.vault_ata, &creator_ata, &p.vault, p.prize_amount)
+        .unwrap();
+    p.mint
+        .transfer(&mut p.svm, &p.vault_ata, &creator_ata, &p.vault, creator_cut)
+        .unwrap();
+    p.mint.transfer(&mut p.svm, &p.vault_ata, &mega_ata, &p.vault, mega_cut).unwrap();
+
+    assert_eq!(p.svm.token_amount(&p.vault_ata), 0);
+    assert!(p.svm.token_amount(&mega_ata) > 0);
+}
+
+#[test]
+fn pool_is_balance_above_the_prize() {
+    assert_eq!(fees::vault_pool(1_500, 1_000), Some(500));
+    assert_eq!(fees::vault_pool(1_000, 1_000), Some(0));
+    assert_eq!(fees::vault_pool(999, 1_000), None);
+}
+
+/// Token-2022's fee on a transfer of `amount` (rounded up, capped at `MAX_FEE`).
+fn transfer_fee(amount: u64) -> u64 {
+    (amount * FEE_BPS as u64).div_ceil(10_000).min(MAX_FEE)
+}
+
+struct Game {
+    rt: Runtime,
+    mint: Pubkey,
+    creator: solana_keypair::Keypair,
+    player: solana_keypair::Keypair,
+    vault: Pubkey,
+}
+
+/// A vault over a fee-charging mint with a `prize` deposit, and a funded player.
+fn game(prize: u64) -> Game {
+    let mut rt = Runtime::new();
+    let mint = rt.create_fee_mint(FEE_BPS, MAX_FEE);
+    rt.register_fee_mint(&mint, 0, 0);
+    let creator = rt.wallet();
+    let player = rt.wallet();
+    rt.fund(&creator.pubkey(), &mint, prize);
+    rt.fund(&player.pubkey(), &mint, 10_000 * SOL);
+
+    let args = runtime::vault_args(rt.now(), Some(mint), prize, 40_000_000);
+    let (vault, _) = rt.create_vault(&creator, args).unwrap();
+    Game {
+        rt,
+        mint,
+        creator,
+        player,
+        vault,
+    }
+}
+
+impl Game {
+    fn vault_ata(&self) -> Pubkey {
+        runtime::ata(&self.vault, &self.mint)
+    }
+
+    fn balance(&self, owner: &Pubkey) -> u64 {
+        self.rt.token_amount(&runtime::ata(owner, &self.mint))
+    }
+
+    /// `n` wrong guesses by the player; returns their `GuessMade` events.
+    fn guess(&mut self, n: usize) -> Vec<GuessMade> {
+        let player = self.player.insecure_clone();
+        (0..n)
+            .map(|_| self.rt.make_guess_spl(&self.vault, &player).unwrap().event::<GuessMade>())
+            .collect()
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_create_and_guess_book_what_arrived() {
+    let prize = 1_000 * SOL;
+    let mut g = game(prize);
+    let vault = g.rt.get::<Vault>(&g.vault);
+    assert_eq!(vault.prize_amount, prize - transfer_fee(prize));
+    assert_eq!(g.rt.token_amount(&g.vault_ata()), vault.prize_amount);
+
+    let mega = runtime::pda(&[b"mega_vault"]);
+    let mega_before = g.balance(&mega);
+    let events = g.guess(3);
+    let vault = g.rt.get::<Vault>(&g.vault);
+    for event in &events {
+        let parts = vault.fee_split.split(event.fee);
+        assert_eq!(event.winner_cut, parts.winner - transfer_fee(parts.winner));
+        assert_eq!(event.mega_cut, parts.mega - transfer_fee(parts.mega));
+    }
+    let winner_cuts: u64 = events.iter().map(|e| e.winner_cut).sum();
+    let mega_cuts: u64 = events.iter().map(|e| e.mega_cut).sum();
+    assert_eq!(vault.winner_fee_pool, winner_cuts);
+    assert_eq!(g.rt.token_amount(&g.vault_ata()), vault.prize_amount + winner_cuts);
+    assert_eq!(g.balance(&mega) - mega_before, mega_cuts);
+    let season = g.rt.get::<MegaSeason>(&runtime::mega_season_pda(&g.mint, 0));
+    assert_eq!(season.fee_inflows, mega_cuts);
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_claim_pays_prize_and_pool_net_of_fees() {
+    let mut g = game(1_000 * SOL);
+    g.guess(5);
+    let vault = g.rt.get::<Vault>(&g.vault);
+    let (creator, player) = (g.creator.insecure_clone(), g.player.insecure_clone());
+    g.rt.win(&g.vault, &creator, &player);
+
+    let before = g.balance(&player.pubkey());
+    let claimed = g.rt.claim_prize(&g.vault, &player).unwrap().event::<PrizeClaimed>();
+
+    let received = g.balance(&player.pubkey()) - before;
+    assert_eq!(claimed.amount, received);
+    let pool = vault.winner_fee_pool;
+    assert_eq!(
+        received,
+        vault.prize_amount - transfer_fee(vault.prize_amount) + pool - transfer_fee(pool)
+    );
+    assert_eq!(g.rt.token_amount(&g.vault_ata()), 0);
+    assert!(g.rt.get::<Vault>(&g.vault).paid_out);
+}
+
+#[test]
+#[ignore = "needs target/deploy/vault_game.so (anchor build)"]
+fn handler_reclaim_splits_the_pool_net_of_fees() {
+    let mut g = game(500 * SOL);
+    g.guess(7);
+    let vault = g.rt.get::<Vault>(&g.vault);
+    let creator = g.creator.insecure_clone();
+    g.rt.expire(&g.vault, &creator);
+
+    let mega = runtime::pda(&[b"mega_vault"]);
+    let before = (g.balance(&creator.pubkey()), g.balance(&mega));
+    let reclaimed = g.rt.reclaim_prize(&g.vault, &creator).unwrap().event::<PrizeReclaimed>();
+
+    let returned = g.balance(&creator.pubkey()) - before.0;
+    let mega_cut = g.balance(&mega) - before.1;
+    assert_eq!((reclaimed.amount, reclaimed.mega_cut), (returned, mega_cut));
+    let pool = vault.winner_fee_pool;
+    assert_eq!(
+        returned,
+        vault.prize_amount - transfer_fee(vault.prize_amount) + pool / 2 - transfer_fee(pool / 2)
+    );
+    assert_eq!(mega_cut, pool - pool / 2 - transfer_fee(pool - pool / 2));
+    assert_eq!(g.rt.token_amount(&g.vault_ata()), 0);
+    let season = g.rt.get::<MegaSeason>(&runtime::mega_season_pda(&g.mint, 0));
+    assert_eq!(season.reclaim_inflows, mega_cut);
+}